@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    document::Document,
+    objects::{Dictionary, Name, Object},
+};
+
+/// The glyph widths of a font, in thousandths of text space units.
+///
+/// Simple fonts use one byte per character code. Composite fonts are assumed to use two, as the
+/// `Identity-H` encoding does; other CMaps are not read.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Widths {
+    two_byte: bool,
+    widths: BTreeMap<u32, f64>,
+    /// The `c_first c_last w` ranges of a CIDFont by their first CID, kept as ranges since a
+    /// single one may cover every CID.
+    ranges: BTreeMap<u32, (u32, f64)>,
+    default: f64,
+}
+
+impl Widths {
+    pub(super) fn new<'b>(document: &Document<'b>, font: &Dictionary<'b>) -> Self {
+        let subtype = get(document, font, b"Subtype").and_then(Object::as_name);
+        if subtype == Some(b"Type0") {
+            let descendant = get(document, font, b"DescendantFonts")
+                .and_then(Object::as_array)
+                .and_then(|fonts| fonts.get().first())
+                .and_then(|font| document.resolve(font).ok())
+                .and_then(Object::as_dict);
+            let Some(descendant) = descendant else {
+                return Self {
+                    two_byte: true,
+                    default: 1000.0,
+                    ..Self::default()
+                };
+            };
+            let default = get(document, descendant, b"DW")
+                .and_then(Object::as_f64)
+                .unwrap_or(1000.0);
+            let mut widths = Self {
+                two_byte: true,
+                default,
+                ..Self::default()
+            };
+            if let Some(w) = get(document, descendant, b"W").and_then(Object::as_array) {
+                widths.read_cid_widths(document, w.get());
+            }
+            return widths;
+        }
+        // Type 3 glyph widths are in glyph space, which the font matrix maps to text space.
+        let scale = match get(document, font, b"FontMatrix").and_then(Object::as_array) {
+            Some(matrix) if subtype == Some(b"Type3") => matrix
+                .get()
+                .first()
+                .and_then(Object::as_f64)
+                .map_or(1.0, |a| a * 1000.0),
+            _ => 1.0,
+        };
+        let default = get(document, font, b"FontDescriptor")
+            .and_then(Object::as_dict)
+            .and_then(|descriptor| get(document, descriptor, b"MissingWidth"))
+            .and_then(Object::as_f64)
+            .unwrap_or(0.0);
+        let first_char = get(document, font, b"FirstChar")
+            .and_then(Object::as_i64)
+            .and_then(|first| u32::try_from(first).ok())
+            .unwrap_or(0);
+        let widths = get(document, font, b"Widths")
+            .and_then(Object::as_array)
+            .map(|widths| {
+                (first_char..=u32::from(u8::MAX))
+                    .zip(widths.get())
+                    .filter_map(|(code, width)| {
+                        let width = document.resolve(width).ok()?.as_f64()?;
+                        Some((code, width * scale))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            two_byte: false,
+            widths,
+            ranges: BTreeMap::new(),
+            default: default * scale,
+        }
+    }
+    /// Splits a string into character codes and their widths.
+    pub(super) fn glyphs(&self, text: &[u8]) -> Vec<(u32, f64)> {
+        let code = |bytes: &[u8]| match *bytes {
+            [high, low] => u32::from(u16::from_be_bytes([high, low])),
+            [byte] => u32::from(byte),
+            _ => 0,
+        };
+        let size = if self.two_byte { 2 } else { 1 };
+        text.chunks(size)
+            .map(code)
+            .map(|code| (code, self.width(code)))
+            .collect()
+    }
+    fn width(&self, code: u32) -> f64 {
+        if let Some(width) = self.widths.get(&code) {
+            return *width;
+        }
+        match self.ranges.range(..=code).next_back() {
+            Some((_, (last, width))) if code <= *last => *width,
+            _ => self.default,
+        }
+    }
+    /// Whether word spacing applies to `code`, which it does for the single-byte code 32 only.
+    pub(super) fn is_space(&self, code: u32) -> bool {
+        !self.two_byte && code == 32
+    }
+    /// Reads the `/W` array of a CIDFont, made of `c [w1 w2 ...]` and `c_first c_last w` entries.
+    /// CIDs beyond two bytes are ignored.
+    fn read_cid_widths<'b>(&mut self, document: &Document<'b>, w: &[Object<'b>]) {
+        let cid = |object: &Object| {
+            object
+                .as_i64()
+                .and_then(|cid| u16::try_from(cid).ok())
+                .map(u32::from)
+        };
+        let mut entries = w.iter();
+        while let Some(first) = entries.next() {
+            let Some(first) = cid(first) else {
+                return;
+            };
+            let Some(next) = entries.next().and_then(|next| document.resolve(next).ok()) else {
+                return;
+            };
+            if let Some(list) = next.as_array() {
+                for (cid, width) in (first..=u32::from(u16::MAX)).zip(list.get()) {
+                    if let Some(width) = width.as_f64() {
+                        self.widths.insert(cid, width);
+                    }
+                }
+                continue;
+            }
+            let (Some(last), Some(width)) = (cid(next), entries.next().and_then(Object::as_f64))
+            else {
+                return;
+            };
+            if first <= last {
+                self.ranges.insert(first, (last, width));
+            }
+        }
+    }
+}
+
+fn get<'a, 'b>(
+    document: &'a Document<'b>,
+    dict: &'a Dictionary<'b>,
+    key: &[u8],
+) -> Option<&'a Object<'b>> {
+    dict.get(&Name::new(key))
+        .and_then(|value| document.resolve(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::tests::build_pdf, objects::ObjectId};
+    #[test]
+    fn widths_simple() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "<</Type /Font /Subtype /TrueType /FirstChar 65 /Widths [600 700] \
+                 /FontDescriptor <</MissingWidth 250>>>>",
+            ],
+            "<</Size 3 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let font = document.get_object(ObjectId::new(2, 0)).unwrap();
+        let widths = Widths::new(&document, font.as_dict().unwrap());
+        assert_eq!(
+            widths.glyphs(b"AB "),
+            [(65, 600.0), (66, 700.0), (32, 250.0)]
+        );
+        assert!(widths.is_space(32));
+    }
+    #[test]
+    fn widths_composite() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "<</Type /Font /Subtype /Type0 /DescendantFonts [3 0 R]>>",
+                "<</Subtype /CIDFontType2 /DW 900 /W [1 [500 600] 10 12 300]>>",
+            ],
+            "<</Size 4 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let font = document.get_object(ObjectId::new(2, 0)).unwrap();
+        let widths = Widths::new(&document, font.as_dict().unwrap());
+        assert_eq!(
+            widths.glyphs(b"\x00\x01\x00\x02\x00\x0b\x00\x20"),
+            [(1, 500.0), (2, 600.0), (11, 300.0), (32, 900.0)]
+        );
+        assert!(!widths.is_space(32));
+    }
+    #[test]
+    fn widths_full_range() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "<</Type /Font /Subtype /Type0 /DescendantFonts [3 0 R]>>",
+                "<</Subtype /CIDFontType2 /W [0 65535 250 1 [500] 10 5 300]>>",
+            ],
+            "<</Size 4 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let font = document.get_object(ObjectId::new(2, 0)).unwrap();
+        let widths = Widths::new(&document, font.as_dict().unwrap());
+        assert_eq!(
+            widths.glyphs(b"\x00\x01\x00\x0a\xff\xff"),
+            [(1, 500.0), (10, 250.0), (65535, 250.0)]
+        );
+    }
+}
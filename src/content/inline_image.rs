@@ -0,0 +1,164 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use nom::{Parser, bytes::complete::take_while_m_n, error::context, sequence::preceded};
+
+use crate::{
+    error::{ParseError, ParseResult},
+    lexer::{is_regular, is_whitespace, keyword, space0},
+    limits::Limits,
+    objects::{Dictionary, Name, Object, Stream},
+};
+
+/// How many bytes after a candidate `EI` must look like content rather than image data.
+const LOOKAHEAD: usize = 8;
+
+/// Parses an inline image after its `BI` operator, up to and including `EI`. The image
+/// dictionary and data are returned as a stream, without a `/Length` of its own.
+pub(super) fn parse_inline_image<'b>(
+    input: &'b [u8],
+    limits: &Limits,
+) -> ParseResult<'b, Stream<'b>> {
+    let mut info = Dictionary::new();
+    let (mut rem, ()) = space0(input)?;
+    let data = loop {
+        if let Ok((data, _)) = (keyword("ID"), take_while_m_n(1, 1, is_whitespace)).parse(rem) {
+            break data;
+        }
+        let (value, key) =
+            context("expected a name or ID in the inline image", Name::parse).parse(rem)?;
+        let (next, value) = preceded(space0, |i| Object::parse_limited(i, limits)).parse(value)?;
+        info.insert(key, value);
+        (rem, ()) = space0(next)?;
+    };
+    let length = info
+        .get(&Name::new(b"L"))
+        .or_else(|| info.get(&Name::new(b"Length")))
+        .and_then(Object::as_i64)
+        .and_then(|length| usize::try_from(length).ok());
+    let declared = length.and_then(|length| {
+        let (data, rem) = data.split_at_checked(length)?;
+        let (rem, _) = preceded(space0, keyword("EI")).parse(rem).ok()?;
+        Some((rem, data))
+    });
+    let (rem, data) = declared.or_else(|| find_end(data)).ok_or_else(|| {
+        nom::Err::Error(ParseError::with_reason(
+            data,
+            "missing EI after the inline image data",
+        ))
+    })?;
+    Ok((
+        rem,
+        Stream {
+            info,
+            data: Cow::Borrowed(data),
+        },
+    ))
+}
+
+/// Searches for an `EI` that is preceded by whitespace, not followed by a regular character and
+/// followed by what looks like more content, since the image data may itself contain `EI`.
+/// Returns the input after `EI` along with the data before the whitespace.
+fn find_end(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut start = 0;
+    while let Some(found) = input
+        .get(start..)?
+        .windows(2)
+        .position(|w| w == b"EI")
+        .and_then(|i| i.checked_add(start))
+    {
+        let data = found.checked_sub(1).and_then(|end| input.get(..end));
+        let preceded = found
+            .checked_sub(1)
+            .and_then(|i| input.get(i))
+            .is_some_and(|c| is_whitespace(*c));
+        let rem = input.get(found.saturating_add(2)..).unwrap_or_default();
+        let terminated = rem.first().is_none_or(|c| !is_regular(*c));
+        let textual = rem
+            .iter()
+            .take(LOOKAHEAD)
+            .all(|c| c.is_ascii_graphic() || is_whitespace(*c));
+        if let Some(data) = data
+            && preceded
+            && terminated
+            && textual
+        {
+            return Some((rem, data));
+        }
+        start = found.saturating_add(2);
+    }
+    None
+}
+
+/// Writes an inline image as `BI`, the entries of its dictionary, `ID`, its data and `EI`.
+pub(super) fn write_inline_image(image: &Stream<'_>, w: &mut impl Write) -> io::Result<()> {
+    w.write_all(b"BI")?;
+    for (key, value) in image.get_info().iter() {
+        w.write_all(b" ")?;
+        key.write_to(w)?;
+        w.write_all(b" ")?;
+        value.write_to(w)?;
+    }
+    w.write_all(b" ID ")?;
+    w.write_all(image.get_data())?;
+    w.write_all(b"\nEI")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{Content, Operation};
+
+    fn image<'a, 'b>(content: &'a Content<'b>, i: usize) -> &'a Stream<'b> {
+        let operation: &Operation = &content.get()[i];
+        assert_eq!(operation.get_operator(), "BI");
+        operation.get_operands()[0].as_stream().unwrap()
+    }
+    #[test]
+    fn inline_image_1() {
+        let input = b"q BI /W 2 /H 1 /BPC 8 /CS /G ID \x00\xff\nEI Q";
+        let (rem, content) = Content::parse(input).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(content.get().len(), 3);
+        let image = image(&content, 1);
+        assert_eq!(image.get_data(), b"\x00\xff");
+        assert_eq!(image.get_info().len(), 4);
+        assert_eq!(content.get()[2].get_operator(), "Q");
+    }
+    #[test]
+    fn inline_image_ei_in_data() {
+        // The first EI is followed by binary data, the second by a content operator.
+        let input = b"BI /W 4 /H 1 /CS /G ID a EI\x01\x02\x03 EI Q";
+        let (_, content) = Content::parse(input).unwrap();
+        assert_eq!(image(&content, 0).get_data(), b"a EI\x01\x02\x03");
+        assert_eq!(content.get()[1].get_operator(), "Q");
+    }
+    #[test]
+    fn inline_image_length() {
+        let input = b"BI /W 4 /H 1 /L 4 ID \nEI EI Q";
+        let (_, content) = Content::parse(input).unwrap();
+        assert_eq!(image(&content, 0).get_data(), b"\nEI ");
+        assert_eq!(content.get()[1].get_operator(), "Q");
+    }
+    #[test]
+    fn inline_image_error() {
+        let input = b"BI /W 1 /H 1 ID \x00";
+        let err = match Content::parse(input) {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "missing EI after the inline image data");
+        assert!(Content::parse(b"BI /W 1 (H) 1 ID \x00 EI").is_err());
+    }
+    #[test]
+    fn write_inline_image_1() {
+        let input = b"BI /W 2 /H 1 /CS /G ID \x00\xff\nEI";
+        let (_, content) = Content::parse(input).unwrap();
+        let output = content.encode();
+        assert_eq!(output, b"BI /W 2 /H 1 /CS /G ID \x00\xff\nEI\n");
+        assert_eq!(Content::parse(&output).unwrap().1, content);
+    }
+}
@@ -0,0 +1,880 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use super::{
+    Clip, Color, ColorSpace, Content, FillRule, GraphicsState, Operation, Paint, Path,
+    font::Widths,
+    state::{dash, set},
+};
+use crate::{
+    document::{Document, Page},
+    error::{Error, Result},
+    geometry::{Matrix, Rectangle},
+    objects::{Dictionary, Name, Object, Stream},
+};
+
+/// A glyph shown by a text operator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub code: u32,
+    /// The text rendering matrix, mapping the glyph from text space to the initial user space.
+    pub matrix: Matrix,
+    /// The horizontal displacement in unscaled text space units, spacing included.
+    pub width: f64,
+}
+
+/// Receives what an [`Interpreter`] draws. Every method does nothing by default.
+pub trait Handler {
+    /// Called when a path is stroked or filled, before any pending clip takes effect.
+    fn path(&mut self, _path: &Path, _paint: Paint, _state: &GraphicsState<'_, '_>) {}
+    /// Called for each string shown by `Tj`, `'`, `"` and `TJ`.
+    fn text(&mut self, _glyphs: &[Glyph], _state: &GraphicsState<'_, '_>) {}
+    /// Called for image XObjects painted with `Do`.
+    fn image(&mut self, _image: &Stream<'_>, _state: &GraphicsState<'_, '_>) {}
+    /// Called for inline images, whose dictionary may use abbreviated keys and values.
+    fn inline_image(&mut self, _image: &Stream<'_>, _state: &GraphicsState<'_, '_>) {}
+    fn begin_marked_content(&mut self, _tag: &Name<'_>, _properties: Option<&Dictionary<'_>>) {}
+    fn end_marked_content(&mut self) {}
+    /// Called for the `MP` and `DP` marked-content points.
+    fn marked_content_point(&mut self, _tag: &Name<'_>, _properties: Option<&Dictionary<'_>>) {}
+}
+
+/// Runs content streams, tracking the graphics state and reporting what is drawn to a
+/// [`Handler`]. Operators with missing or invalid operands are ignored, as are unknown ones.
+/// Text rendered with a clipping mode does not add to the clipping path.
+#[derive(Debug, Clone)]
+pub struct Interpreter<'a, 'b> {
+    document: &'a Document<'b>,
+    resources: Option<&'a Dictionary<'b>>,
+    state: GraphicsState<'a, 'b>,
+    saved: Vec<GraphicsState<'a, 'b>>,
+    /// How many saved states belong to enclosing form XObjects and may not be restored by `Q`.
+    floor: usize,
+    /// The form XObjects being run, innermost last.
+    forms: Vec<&'a Stream<'b>>,
+    /// The decoded content of every form XObject run so far, by the address of its stream.
+    form_contents: BTreeMap<usize, Rc<Content<'static>>>,
+    operations: usize,
+    path: Path,
+    clip: Option<FillRule>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+    fonts: Vec<(&'a Dictionary<'b>, Rc<Widths>)>,
+}
+
+impl<'a, 'b> Interpreter<'a, 'b> {
+    pub fn new(document: &'a Document<'b>, resources: Option<&'a Dictionary<'b>>) -> Self {
+        Self {
+            document,
+            resources,
+            state: GraphicsState::new(Matrix::identity()),
+            saved: Vec::new(),
+            floor: 0,
+            forms: Vec::new(),
+            form_contents: BTreeMap::new(),
+            operations: 0,
+            path: Path::new(),
+            clip: None,
+            text_matrix: Matrix::identity(),
+            line_matrix: Matrix::identity(),
+            fonts: Vec::new(),
+        }
+    }
+    pub fn get_state(&self) -> &GraphicsState<'a, 'b> {
+        &self.state
+    }
+    /// The current state, for instance to start from a CTM other than the identity.
+    pub fn get_state_mut(&mut self) -> &mut GraphicsState<'a, 'b> {
+        &mut self.state
+    }
+    pub fn run(&mut self, content: &Content<'_>, handler: &mut impl Handler) -> Result<()> {
+        for operation in content.get() {
+            self.execute(operation, handler)?;
+        }
+        Ok(())
+    }
+    /// Runs a single operation. Fails once the interpreter has run more operations than the
+    /// document limits allow.
+    pub fn execute(&mut self, operation: &Operation<'_>, handler: &mut impl Handler) -> Result<()> {
+        self.operations = self.operations.saturating_add(1);
+        if self.operations > self.document.get_limits().max_operations {
+            return Err(Error::LimitExceeded("operation count"));
+        }
+        let operands = operation.get_operands();
+        match operation.get_operator() {
+            // Special graphics state
+            "q" => self.saved.push(self.state.clone()),
+            "Q" => {
+                if self.saved.len() > self.floor
+                    && let Some(state) = self.saved.pop()
+                {
+                    self.state = state;
+                }
+            }
+            "cm" => {
+                if let Some([a, b, c, d, e, f]) = numbers(operands) {
+                    self.state.ctm = Matrix::new(a, b, c, d, e, f).multiply(&self.state.ctm);
+                }
+            }
+            // General graphics state
+            "w" => set(&mut self.state.line_width, number(operands)),
+            "J" => set(&mut self.state.line_cap, integer(operands)),
+            "j" => set(&mut self.state.line_join, integer(operands)),
+            "M" => set(&mut self.state.miter_limit, number(operands)),
+            "d" => {
+                if let [array, phase] = operands
+                    && let Some((array, phase)) = dash(array).zip(phase.as_f64())
+                {
+                    self.state.dash_array = array;
+                    self.state.dash_phase = phase;
+                }
+            }
+            "ri" => {
+                if let Some(intent) = name(operands) {
+                    self.state.rendering_intent = intent.clone().into_owned();
+                }
+            }
+            "i" => set(&mut self.state.flatness, number(operands)),
+            "gs" => {
+                let ext_g_state = name(operands)
+                    .and_then(|name| self.resource(b"ExtGState", name))
+                    .and_then(Object::as_dict);
+                if let Some(ext_g_state) = ext_g_state {
+                    self.state.apply(self.document, ext_g_state);
+                }
+            }
+            // Color
+            "CS" | "cs" => {
+                let space = name(operands).and_then(|name| self.color_space(name));
+                if let Some(space) = space {
+                    let color = space.initial_color();
+                    if operation.get_operator() == "CS" {
+                        self.state.stroke_color_space = space;
+                        self.state.stroke_color = color;
+                    } else {
+                        self.state.fill_color_space = space;
+                        self.state.fill_color = color;
+                    }
+                }
+            }
+            "SC" | "SCN" => set(&mut self.state.stroke_color, color(operands)),
+            "sc" | "scn" => set(&mut self.state.fill_color, color(operands)),
+            "G" | "RG" | "K" => {
+                if let Some((space, color)) = device_color(operation) {
+                    self.state.stroke_color_space = space;
+                    self.state.stroke_color = color;
+                }
+            }
+            "g" | "rg" | "k" => {
+                if let Some((space, color)) = device_color(operation) {
+                    self.state.fill_color_space = space;
+                    self.state.fill_color = color;
+                }
+            }
+            // Path construction
+            "m" => {
+                if let Some([x, y]) = numbers(operands) {
+                    self.path.move_to(x, y);
+                }
+            }
+            "l" => {
+                if let Some([x, y]) = numbers(operands) {
+                    self.path.line_to(x, y);
+                }
+            }
+            "c" => {
+                if let Some([x1, y1, x2, y2, x3, y3]) = numbers(operands) {
+                    self.path.curve_to(x1, y1, x2, y2, x3, y3);
+                }
+            }
+            "v" => {
+                if let Some([x2, y2, x3, y3]) = numbers(operands)
+                    && let Some((x1, y1)) = self.path.current_point()
+                {
+                    self.path.curve_to(x1, y1, x2, y2, x3, y3);
+                }
+            }
+            "y" => {
+                if let Some([x1, y1, x3, y3]) = numbers(operands) {
+                    self.path.curve_to(x1, y1, x3, y3, x3, y3);
+                }
+            }
+            "h" => self.path.close(),
+            "re" => {
+                if let Some([x, y, width, height]) = numbers(operands) {
+                    self.path.rectangle(x, y, width, height);
+                }
+            }
+            // Path painting
+            "S" => self.paint(true, None, handler),
+            "s" => {
+                self.path.close();
+                self.paint(true, None, handler);
+            }
+            "f" | "F" => self.paint(false, Some(FillRule::NonZero), handler),
+            "f*" => self.paint(false, Some(FillRule::EvenOdd), handler),
+            "B" => self.paint(true, Some(FillRule::NonZero), handler),
+            "B*" => self.paint(true, Some(FillRule::EvenOdd), handler),
+            "b" => {
+                self.path.close();
+                self.paint(true, Some(FillRule::NonZero), handler);
+            }
+            "b*" => {
+                self.path.close();
+                self.paint(true, Some(FillRule::EvenOdd), handler);
+            }
+            "n" => self.paint(false, None, handler),
+            // Clipping
+            "W" => self.clip = Some(FillRule::NonZero),
+            "W*" => self.clip = Some(FillRule::EvenOdd),
+            // Text objects and positioning
+            "BT" => {
+                self.text_matrix = Matrix::identity();
+                self.line_matrix = Matrix::identity();
+            }
+            "Td" => {
+                if let Some([tx, ty]) = numbers(operands) {
+                    self.next_line(tx, ty);
+                }
+            }
+            "TD" => {
+                if let Some([tx, ty]) = numbers(operands) {
+                    self.state.text.leading = -ty;
+                    self.next_line(tx, ty);
+                }
+            }
+            "Tm" => {
+                if let Some([a, b, c, d, e, f]) = numbers(operands) {
+                    self.text_matrix = Matrix::new(a, b, c, d, e, f);
+                    self.line_matrix = self.text_matrix;
+                }
+            }
+            "T*" => self.next_line(0.0, -self.state.text.leading),
+            // Text state
+            "Tc" => set(&mut self.state.text.char_spacing, number(operands)),
+            "Tw" => set(&mut self.state.text.word_spacing, number(operands)),
+            "Tz" => {
+                if let Some(scale) = number(operands) {
+                    self.state.text.horizontal_scaling = scale / 100.0;
+                }
+            }
+            "TL" => set(&mut self.state.text.leading, number(operands)),
+            "Tf" => {
+                if let [Object::Name(font), size] = operands
+                    && let Some(size) = size.as_f64()
+                {
+                    let font = self.resource(b"Font", font).and_then(Object::as_dict);
+                    self.state.text.font = font;
+                    self.state.text.font_size = size;
+                }
+            }
+            "Tr" => set(&mut self.state.text.render_mode, integer(operands)),
+            "Ts" => set(&mut self.state.text.rise, number(operands)),
+            // Text showing
+            "Tj" => self.show(operands.last(), handler),
+            "'" => {
+                self.next_line(0.0, -self.state.text.leading);
+                self.show(operands.last(), handler);
+            }
+            "\"" => {
+                if let [word_spacing, char_spacing, text] = operands
+                    && let Some((word, character)) =
+                        word_spacing.as_f64().zip(char_spacing.as_f64())
+                {
+                    self.state.text.word_spacing = word;
+                    self.state.text.char_spacing = character;
+                    self.next_line(0.0, -self.state.text.leading);
+                    self.show(Some(text), handler);
+                }
+            }
+            "TJ" => self.show(operands.last(), handler),
+            // XObjects and inline images
+            "Do" => {
+                let xobject = name(operands)
+                    .and_then(|name| self.resource(b"XObject", name))
+                    .and_then(Object::as_stream);
+                if let Some(xobject) = xobject {
+                    self.draw_xobject(xobject, handler)?;
+                }
+            }
+            "BI" => {
+                if let [Object::Stream(image)] = operands {
+                    handler.inline_image(image, &self.state);
+                }
+            }
+            // Marked content
+            "BMC" => {
+                if let Some(tag) = name(operands) {
+                    handler.begin_marked_content(tag, None);
+                }
+            }
+            "BDC" => {
+                if let [Object::Name(tag), properties] = operands {
+                    handler.begin_marked_content(tag, self.properties(properties));
+                }
+            }
+            "EMC" => handler.end_marked_content(),
+            "MP" => {
+                if let Some(tag) = name(operands) {
+                    handler.marked_content_point(tag, None);
+                }
+            }
+            "DP" => {
+                if let [Object::Name(tag), properties] = operands {
+                    handler.marked_content_point(tag, self.properties(properties));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    /// Looks up a named resource, such as a font or an XObject, in a category of the resources.
+    fn resource(&self, category: &[u8], name: &Name<'_>) -> Option<&'a Object<'b>> {
+        let resources = self.resources?.get(&Name::new(category))?;
+        let resource = self
+            .document
+            .resolve(resources)
+            .ok()?
+            .as_dict()?
+            .get(name)?;
+        self.document.resolve(resource).ok()
+    }
+    fn color_space(&self, name: &Name<'_>) -> Option<ColorSpace<'a, 'b>> {
+        match self.resource(b"ColorSpace", name) {
+            Some(definition) => ColorSpace::new(self.document, definition),
+            None => ColorSpace::from_name(name.get()),
+        }
+    }
+    /// The properties of marked content, either inline or named in the `/Properties` resources.
+    fn properties<'x>(&self, properties: &'x Object<'x>) -> Option<&'x Dictionary<'x>>
+    where
+        'a: 'x,
+        'b: 'x,
+    {
+        match properties {
+            Object::Dictionary(properties) => Some(properties),
+            Object::Name(name) => self.resource(b"Properties", name)?.as_dict(),
+            _ => None,
+        }
+    }
+    /// Reports the current path if it is painted, then applies any pending clip and ends the
+    /// path.
+    fn paint(&mut self, stroke: bool, fill: Option<FillRule>, handler: &mut impl Handler) {
+        let path = std::mem::take(&mut self.path);
+        if !path.is_empty() && (stroke || fill.is_some()) {
+            handler.path(&path, Paint { stroke, fill }, &self.state);
+        }
+        if let Some(rule) = self.clip.take() {
+            self.state.clip.push(Clip {
+                path: path.transform(&self.state.ctm),
+                rule,
+            });
+        }
+    }
+    fn next_line(&mut self, tx: f64, ty: f64) {
+        self.line_matrix = Matrix::translate(tx, ty).multiply(&self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+    /// Shows a string or the strings and adjustments of a `TJ` array, advancing the text matrix.
+    fn show(&mut self, text: Option<&Object<'_>>, handler: &mut impl Handler) {
+        let elements = match text {
+            Some(Object::Array(array)) => array.get(),
+            Some(text) => std::slice::from_ref(text),
+            None => return,
+        };
+        let widths = self.widths();
+        let text = &self.state.text;
+        let mut glyphs = Vec::new();
+        for element in elements {
+            if let Some(adjustment) = element.as_f64() {
+                let tx = -adjustment / 1000.0 * text.font_size * text.horizontal_scaling;
+                self.text_matrix = Matrix::translate(tx, 0.0).multiply(&self.text_matrix);
+                continue;
+            }
+            let Some(string) = element.as_string() else {
+                continue;
+            };
+            for (code, width) in widths.glyphs(string) {
+                let parameters = Matrix::new(
+                    text.font_size * text.horizontal_scaling,
+                    0.0,
+                    0.0,
+                    text.font_size,
+                    0.0,
+                    text.rise,
+                );
+                let matrix = parameters
+                    .multiply(&self.text_matrix)
+                    .multiply(&self.state.ctm);
+                let mut spacing = text.char_spacing;
+                if widths.is_space(code) {
+                    spacing += text.word_spacing;
+                }
+                let width = width / 1000.0 * text.font_size + spacing;
+                let tx = width * text.horizontal_scaling;
+                self.text_matrix = Matrix::translate(tx, 0.0).multiply(&self.text_matrix);
+                glyphs.push(Glyph {
+                    code,
+                    matrix,
+                    width,
+                });
+            }
+        }
+        if !glyphs.is_empty() {
+            handler.text(&glyphs, &self.state);
+        }
+    }
+    /// The widths of the current font, read once per font.
+    fn widths(&mut self) -> Rc<Widths> {
+        let Some(font) = self.state.text.font else {
+            return Rc::default();
+        };
+        if let Some((_, widths)) = self.fonts.iter().find(|(f, _)| std::ptr::eq(*f, font)) {
+            return Rc::clone(widths);
+        }
+        let widths = Rc::new(Widths::new(self.document, font));
+        self.fonts.push((font, Rc::clone(&widths)));
+        widths
+    }
+    fn draw_xobject(&mut self, xobject: &'a Stream<'b>, handler: &mut impl Handler) -> Result<()> {
+        let info = xobject.get_info();
+        let subtype = info
+            .get(&Name::new(b"Subtype"))
+            .and_then(|subtype| self.document.resolve(subtype).ok())
+            .and_then(Object::as_name);
+        match subtype {
+            Some(b"Image") => handler.image(xobject, &self.state),
+            Some(b"Form") => self.draw_form(xobject, handler)?,
+            _ => {}
+        }
+        Ok(())
+    }
+    /// Runs the content of a form XObject with its own resources, matrix and bounding box,
+    /// restoring the graphics state afterwards. A form that draws itself, directly or through
+    /// other forms, would nest without end and fails like one nested too deeply.
+    fn draw_form(&mut self, form: &'a Stream<'b>, handler: &mut impl Handler) -> Result<()> {
+        let document = self.document;
+        let limits = document.get_limits();
+        if self.forms.len() >= limits.max_depth
+            || self
+                .forms
+                .iter()
+                .any(|running| std::ptr::eq(*running, form))
+        {
+            return Err(Error::LimitExceeded("form nesting depth"));
+        }
+        let content = self.form_content(form)?;
+        let get = |key: &[u8]| {
+            form.get_info()
+                .get(&Name::new(key))
+                .and_then(|value| document.resolve(value).ok())
+        };
+        let matrix = get(b"Matrix")
+            .and_then(Object::as_array)
+            .and_then(|matrix| Matrix::try_from(matrix).ok())
+            .unwrap_or_default();
+        let bbox = get(b"BBox")
+            .and_then(Object::as_array)
+            .and_then(|bbox| Rectangle::try_from(bbox).ok());
+        let resources = get(b"Resources").and_then(Object::as_dict);
+
+        let outer = Outer {
+            resources: self.resources,
+            floor: self.floor,
+            path: std::mem::take(&mut self.path),
+            clip: self.clip.take(),
+            text_matrix: self.text_matrix,
+            line_matrix: self.line_matrix,
+        };
+        self.saved.push(self.state.clone());
+        self.floor = self.saved.len();
+        self.forms.push(form);
+        self.resources = resources.or(self.resources);
+        self.state.ctm = matrix.multiply(&self.state.ctm);
+        if let Some(bbox) = bbox {
+            let mut path = Path::new();
+            path.rectangle(bbox.get_llx(), bbox.get_lly(), bbox.width(), bbox.height());
+            self.state.clip.push(Clip {
+                path: path.transform(&self.state.ctm),
+                rule: FillRule::NonZero,
+            });
+        }
+
+        let result = self.run(&content, handler);
+
+        self.forms.pop();
+        self.saved.truncate(self.floor);
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+        self.resources = outer.resources;
+        self.floor = outer.floor;
+        self.path = outer.path;
+        self.clip = outer.clip;
+        self.text_matrix = outer.text_matrix;
+        self.line_matrix = outer.line_matrix;
+        result
+    }
+    /// The decoded content of a form XObject, decoded only the first time it is drawn.
+    fn form_content(&mut self, form: &'a Stream<'b>) -> Result<Rc<Content<'static>>> {
+        let key = std::ptr::from_ref(form).addr();
+        if let Some(content) = self.form_contents.get(&key) {
+            return Ok(Rc::clone(content));
+        }
+        let content = Rc::new(Content::decode(&[form], self.document.get_limits())?);
+        self.form_contents.insert(key, Rc::clone(&content));
+        Ok(content)
+    }
+}
+
+/// The interpreter state outside the graphics state that a form XObject must not change.
+struct Outer<'a, 'b> {
+    resources: Option<&'a Dictionary<'b>>,
+    floor: usize,
+    path: Path,
+    clip: Option<FillRule>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+}
+
+impl<'b> Document<'b> {
+    /// Runs the content of a page with its resources, starting from the default user space.
+    pub fn interpret_page<'a>(
+        &'a self,
+        page: &Page<'a, 'b>,
+        handler: &mut impl Handler,
+    ) -> Result<()> {
+        let content = self.get_page_content(page)?;
+        Interpreter::new(self, page.get_resources()).run(&content, handler)
+    }
+}
+
+/// The last `N` operands as numbers. Extra operands before them are ignored.
+fn numbers<const N: usize>(operands: &[Object<'_>]) -> Option<[f64; N]> {
+    let start = operands.len().checked_sub(N)?;
+    let mut numbers = [0.0; N];
+    for (number, operand) in numbers.iter_mut().zip(operands.get(start..)?) {
+        *number = operand.as_f64()?;
+    }
+    Some(numbers)
+}
+
+fn number(operands: &[Object<'_>]) -> Option<f64> {
+    operands.last()?.as_f64()
+}
+
+fn integer(operands: &[Object<'_>]) -> Option<i64> {
+    operands.last()?.as_i64()
+}
+
+fn name<'x>(operands: &'x [Object<'_>]) -> Option<&'x Name<'x>> {
+    match operands.last()? {
+        Object::Name(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// The color set by `SC`, `SCN`, `sc` or `scn`: numeric components optionally followed by a
+/// pattern name.
+fn color(operands: &[Object<'_>]) -> Option<Color> {
+    let (components, pattern) = match operands {
+        [components @ .., Object::Name(pattern)] => (components, Some(pattern)),
+        components => (components, None),
+    };
+    let components = components
+        .iter()
+        .map(Object::as_f64)
+        .collect::<Option<Vec<_>>>()?;
+    Some(match pattern {
+        Some(pattern) => Color::with_pattern(components, pattern.clone().into_owned()),
+        None => Color::new(components),
+    })
+}
+
+/// The color space and color set by the `G`, `RG` and `K` operators and their fill variants.
+fn device_color<'a, 'b>(operation: &Operation<'_>) -> Option<(ColorSpace<'a, 'b>, Color)> {
+    let operands = operation.get_operands();
+    let (space, components) = match operation.get_operator() {
+        "G" | "g" => (ColorSpace::DeviceGray, numbers::<1>(operands)?.to_vec()),
+        "RG" | "rg" => (ColorSpace::DeviceRgb, numbers::<3>(operands)?.to_vec()),
+        _ => (ColorSpace::DeviceCmyk, numbers::<4>(operands)?.to_vec()),
+    };
+    Some((space, Color::new(components)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{content::PathSegment, document::tests::build_pdf, limits::Limits};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Path {
+            bbox: Rectangle,
+            paint: Paint,
+            ctm: [f64; 6],
+            stroke: Vec<f64>,
+            fill: Vec<f64>,
+            line_width: f64,
+            fill_alpha: f64,
+            clips: usize,
+        },
+        Text(Vec<(u32, [f64; 6])>),
+        Image([f64; 6]),
+        InlineImage(Vec<u8>),
+        BeginMarkedContent(Vec<u8>, Option<i64>),
+        EndMarkedContent,
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<Event>);
+
+    impl Handler for Recorder {
+        fn path(&mut self, path: &Path, paint: Paint, state: &GraphicsState<'_, '_>) {
+            self.0.push(Event::Path {
+                bbox: path.bounding_box().unwrap(),
+                paint,
+                ctm: state.ctm.get(),
+                stroke: state.stroke_color.get_components().to_vec(),
+                fill: state.fill_color.get_components().to_vec(),
+                line_width: state.line_width,
+                fill_alpha: state.fill_alpha,
+                clips: state.clip.len(),
+            });
+        }
+        fn text(&mut self, glyphs: &[Glyph], _state: &GraphicsState<'_, '_>) {
+            let glyphs = glyphs.iter().map(|g| (g.code, g.matrix.get())).collect();
+            self.0.push(Event::Text(glyphs));
+        }
+        fn image(&mut self, _image: &Stream<'_>, state: &GraphicsState<'_, '_>) {
+            self.0.push(Event::Image(state.ctm.get()));
+        }
+        fn inline_image(&mut self, image: &Stream<'_>, _state: &GraphicsState<'_, '_>) {
+            self.0.push(Event::InlineImage(image.get_data().to_vec()));
+        }
+        fn begin_marked_content(&mut self, tag: &Name<'_>, properties: Option<&Dictionary<'_>>) {
+            let mcid = properties
+                .and_then(|properties| properties.get(&Name::new(b"MCID")))
+                .and_then(Object::as_i64);
+            self.0
+                .push(Event::BeginMarkedContent(tag.get().to_vec(), mcid));
+        }
+        fn end_marked_content(&mut self) {
+            self.0.push(Event::EndMarkedContent);
+        }
+    }
+
+    fn document(content: &str, form: &str) -> Vec<u8> {
+        build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [3 0 R] /Count 1>>",
+                "<</Type /Page /Parent 2 0 R /Resources 4 0 R /Contents 5 0 R>>",
+                "<</Font <</F1 6 0 R>> /ExtGState <</GS1 <</LW 3 /ca 0.5>>>> \
+                 /ColorSpace <</CS0 [/ICCBased 7 0 R]>> /XObject <</Im1 8 0 R /Fm1 9 0 R>> \
+                 /Properties <</MC0 <</MCID 3>>>>>>",
+                &format!("<</Length {}>>stream\n{content}\nendstream", content.len()),
+                "<</Type /Font /Subtype /TrueType /FirstChar 65 /Widths [600 700]>>",
+                "<</N 3 /Length 0>>stream\n\nendstream",
+                "<</Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceGray \
+                 /BitsPerComponent 8 /Length 1>>stream\na\nendstream",
+                &format!(
+                    "<</Type /XObject /Subtype /Form /BBox [0 0 20 20] /Matrix [1 0 0 1 50 50] \
+                     /Length {}>>stream\n{form}\nendstream",
+                    form.len()
+                ),
+            ],
+            "<</Size 10 /Root 1 0 R>>",
+        )
+    }
+
+    fn interpret(input: &[u8]) -> Result<Vec<Event>> {
+        let document = Document::load(input)?;
+        let pages = document.pages()?;
+        let mut recorder = Recorder::default();
+        document.interpret_page(&pages[0], &mut recorder)?;
+        Ok(recorder.0)
+    }
+
+    #[test]
+    fn interpret_graphics() {
+        let content = "q 2 0 0 2 10 20 cm /GS1 gs 0.5 g 0 0 10 10 re f Q\n\
+                       /CS0 cs 0.1 0.2 0.3 sc 1 0 0 RG 5 w 0 0 m 10 0 l S\n\
+                       0 0 5 5 re W n 0 0 m 5 5 l 5 0 v b*\n\
+                       /Im1 Do /Fm1 Do 0 0 1 1 re F Q Q";
+        let events = interpret(&document(content, "0 0 1 1 re f Q Q")).unwrap();
+        let fill = Paint {
+            stroke: false,
+            fill: Some(FillRule::NonZero),
+        };
+        assert_eq!(
+            events,
+            [
+                Event::Path {
+                    bbox: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+                    paint: fill,
+                    ctm: [2.0, 0.0, 0.0, 2.0, 10.0, 20.0],
+                    stroke: vec![0.0],
+                    fill: vec![0.5],
+                    line_width: 3.0,
+                    fill_alpha: 0.5,
+                    clips: 0,
+                },
+                Event::Path {
+                    bbox: Rectangle::new(0.0, 0.0, 10.0, 0.0),
+                    paint: Paint {
+                        stroke: true,
+                        fill: None
+                    },
+                    ctm: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                    stroke: vec![1.0, 0.0, 0.0],
+                    fill: vec![0.1, 0.2, 0.3],
+                    line_width: 5.0,
+                    fill_alpha: 1.0,
+                    clips: 0,
+                },
+                Event::Path {
+                    bbox: Rectangle::new(0.0, 0.0, 5.0, 5.0),
+                    paint: Paint {
+                        stroke: true,
+                        fill: Some(FillRule::EvenOdd)
+                    },
+                    ctm: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                    stroke: vec![1.0, 0.0, 0.0],
+                    fill: vec![0.1, 0.2, 0.3],
+                    line_width: 5.0,
+                    fill_alpha: 1.0,
+                    clips: 1,
+                },
+                Event::Image([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+                // The form is clipped by its bounding box and its `Q` do not restore the page's
+                // saved states.
+                Event::Path {
+                    bbox: Rectangle::new(0.0, 0.0, 1.0, 1.0),
+                    paint: fill,
+                    ctm: [1.0, 0.0, 0.0, 1.0, 50.0, 50.0],
+                    stroke: vec![1.0, 0.0, 0.0],
+                    fill: vec![0.1, 0.2, 0.3],
+                    line_width: 5.0,
+                    fill_alpha: 1.0,
+                    clips: 2,
+                },
+                Event::Path {
+                    bbox: Rectangle::new(0.0, 0.0, 1.0, 1.0),
+                    paint: fill,
+                    ctm: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                    stroke: vec![1.0, 0.0, 0.0],
+                    fill: vec![0.1, 0.2, 0.3],
+                    line_width: 5.0,
+                    fill_alpha: 1.0,
+                    clips: 1,
+                },
+            ]
+        );
+    }
+    #[test]
+    fn interpret_text() {
+        let content = "BT /F1 10 Tf 100 200 Td (AB) Tj [(A) -1000 (B)] TJ\n\
+                       2 Tz 12 TL T* (A) Tj ET";
+        let events = interpret(&document(content, "")).unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::Text(vec![
+                    (65, [10.0, 0.0, 0.0, 10.0, 100.0, 200.0]),
+                    (66, [10.0, 0.0, 0.0, 10.0, 106.0, 200.0])
+                ]),
+                Event::Text(vec![
+                    (65, [10.0, 0.0, 0.0, 10.0, 113.0, 200.0]),
+                    (66, [10.0, 0.0, 0.0, 10.0, 129.0, 200.0])
+                ]),
+                Event::Text(vec![(65, [0.2, 0.0, 0.0, 10.0, 100.0, 188.0])]),
+            ]
+        );
+    }
+    #[test]
+    fn interpret_marked_content() {
+        let content = "/P /MC0 BDC BI /W 1 /H 1 /CS /G /BPC 8 ID a EI EMC /Span <</MCID 4>> BDC";
+        let events = interpret(&document(content, "")).unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::BeginMarkedContent(b"P".to_vec(), Some(3)),
+                Event::InlineImage(b"a".to_vec()),
+                Event::EndMarkedContent,
+                Event::BeginMarkedContent(b"Span".to_vec(), Some(4)),
+            ]
+        );
+    }
+    #[test]
+    fn interpret_recursive_form() {
+        let err = interpret(&document("/Fm1 Do", "/Fm1 Do")).unwrap_err();
+        assert_eq!(err, Error::LimitExceeded("form nesting depth"));
+    }
+    /// A page drawing a chain of `levels` forms, each of which draws the next one twice.
+    fn fan_out_document(levels: usize) -> Vec<u8> {
+        let xobjects: std::string::String = (0..=levels)
+            .map(|i| format!("/F{i} {} 0 R ", i.saturating_add(5)))
+            .collect();
+        let mut objects = vec![
+            "<</Type /Catalog /Pages 2 0 R>>".to_owned(),
+            "<</Type /Pages /Kids [3 0 R] /Count 1>>".to_owned(),
+            format!(
+                "<</Type /Page /Parent 2 0 R /Resources <</XObject <<{xobjects}>>>> \
+                 /Contents 4 0 R>>"
+            ),
+            "<</Length 6>>stream\n/F0 Do\nendstream".to_owned(),
+        ];
+        for i in 0..=levels {
+            let content = if i == levels {
+                "0 0 1 1 re f".to_owned()
+            } else {
+                format!("/F{next} Do /F{next} Do", next = i.saturating_add(1))
+            };
+            objects.push(format!(
+                "<</Subtype /Form /BBox [0 0 1 1] /Length {}>>stream\n{content}\nendstream",
+                content.len()
+            ));
+        }
+        let objects: Vec<&str> = objects.iter().map(std::string::String::as_str).collect();
+        build_pdf(
+            &objects,
+            &format!("<</Size {} /Root 1 0 R>>", levels.saturating_add(6)),
+        )
+    }
+    #[test]
+    fn interpret_form_fan_out() {
+        let events = interpret(&fan_out_document(8)).unwrap();
+        assert_eq!(events.len(), 256);
+        let input = fan_out_document(22);
+        let limits = Limits {
+            max_operations: 10_000,
+            ..Limits::default()
+        };
+        let document = Document::load_with_limits(&input, b"", limits).unwrap();
+        let pages = document.pages().unwrap();
+        let err = document
+            .interpret_page(&pages[0], &mut Recorder::default())
+            .unwrap_err();
+        assert_eq!(err, Error::LimitExceeded("operation count"));
+    }
+    #[test]
+    fn execute_operations() {
+        let input = build_pdf(&["<</Type /Catalog>>"], "<</Size 2 /Root 1 0 R>>");
+        let document = Document::load(&input).unwrap();
+        let mut interpreter = Interpreter::new(&document, None);
+        interpreter.get_state_mut().ctm = Matrix::scale(2.0, 2.0);
+        let (_, content) =
+            Content::parse(b"1 1 m 2 2 l W* n [3 1] 0.5 d 0 0 1 0 k /Unknown cs 7 xyz").unwrap();
+        interpreter.run(&content, &mut Recorder::default()).unwrap();
+        let state = interpreter.get_state();
+        assert_eq!(
+            state.clip[0].path.get(),
+            [PathSegment::MoveTo(2.0, 2.0), PathSegment::LineTo(4.0, 4.0)]
+        );
+        assert_eq!(state.clip[0].rule, FillRule::EvenOdd);
+        assert_eq!(state.dash_array, [3.0, 1.0]);
+        assert_eq!(state.dash_phase, 0.5);
+        assert_eq!(state.fill_color_space, ColorSpace::DeviceCmyk);
+        assert_eq!(state.fill_color.get_components(), [0.0, 0.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,206 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod font;
+mod inline_image;
+mod interpreter;
+mod operation;
+mod path;
+mod state;
+use std::io::{self, Write};
+
+use inline_image::{parse_inline_image, write_inline_image};
+pub use interpreter::*;
+use nom::{Parser, bytes::complete::take_while1, error::context};
+pub use operation::*;
+pub use path::*;
+pub use state::*;
+
+use crate::{
+    error::{Error, ParseResult, Result},
+    lexer::{is_regular, space0},
+    limits::Limits,
+    objects::{Object, Stream},
+};
+
+/// The sequence of operations making up a page or form XObject.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Content<'b> {
+    operations: Vec<Operation<'b>>,
+}
+
+impl<'b> Content<'b> {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Content<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    /// Parses operations until the end of the input. Operands left without an operator at the end
+    /// are dropped.
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Content<'b>> {
+        let mut operations = Vec::new();
+        let mut operands = Vec::new();
+        let (mut rem, ()) = space0(input)?;
+        while !rem.is_empty() {
+            rem = match Object::parse_limited(rem, limits) {
+                Ok((next, operand)) => {
+                    operands.push(operand);
+                    next
+                }
+                Err(nom::Err::Error(_)) => {
+                    let (next, operator) = context(
+                        "expected an operand or an operator",
+                        take_while1(is_regular),
+                    )
+                    .parse(rem)?;
+                    if operator == b"BI" {
+                        let (next, image) = parse_inline_image(next, limits)?;
+                        operations.push(Operation::new("BI", vec![Object::Stream(image)]));
+                        operands.clear();
+                        next
+                    } else {
+                        let operands = std::mem::take(&mut operands);
+                        operations.push(Operation::from_bytes(operator, operands));
+                        next
+                    }
+                }
+                Err(err) => return Err(err),
+            };
+            (rem, ()) = space0(rem)?;
+        }
+        Ok((rem, Content { operations }))
+    }
+    /// Decodes and parses the streams of a `/Contents` array as one, since an operation may
+    /// continue from one stream into the next.
+    pub fn decode(streams: &[&Stream], limits: &Limits) -> Result<Content<'static>> {
+        let mut data = Vec::new();
+        for stream in streams {
+            data.extend(stream.decode_limited(limits)?);
+            data.push(b'\n');
+        }
+        let (_, content) =
+            Content::parse_limited(&data, limits).map_err(|e| Error::from(e).locate(&data))?;
+        Ok(content.into_owned())
+    }
+    pub fn new(operations: Vec<Operation<'b>>) -> Self {
+        Self { operations }
+    }
+    pub fn get(&self) -> &[Operation<'b>] {
+        &self.operations
+    }
+    pub fn get_mut(&mut self) -> &mut Vec<Operation<'b>> {
+        &mut self.operations
+    }
+    pub fn into_owned(self) -> Content<'static> {
+        Content {
+            operations: self
+                .operations
+                .into_iter()
+                .map(Operation::into_owned)
+                .collect(),
+        }
+    }
+    /// Writes every operation on a line of its own.
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        for operation in &self.operations {
+            operation.write_to(w)?;
+            w.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+    /// The content as the data of a content stream.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        // Writing to a `Vec` cannot fail.
+        let _ = self.write_to(&mut data);
+        data
+    }
+}
+
+impl<'b> IntoIterator for Content<'b> {
+    type Item = Operation<'b>;
+    type IntoIter = std::vec::IntoIter<Operation<'b>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.operations.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::{Dictionary, Name, Real};
+
+    use super::*;
+    #[test]
+    fn parse_content_1() {
+        let input = b"q 1 0 0 1 72 720 cm\nBT /F1 12 Tf (Hello) Tj ET\n0 0 m 10 10 l S Q";
+        let (rem, content) = Content::parse(input).unwrap();
+        assert!(rem.is_empty());
+        let operators: Vec<&str> = content.get().iter().map(Operation::get_operator).collect();
+        assert_eq!(
+            operators,
+            ["q", "cm", "BT", "Tf", "Tj", "ET", "m", "l", "S", "Q"]
+        );
+        let cm = &content.get()[1];
+        assert_eq!(cm.get_operands().len(), 6);
+        assert_eq!(cm.get_operands()[4].as_i64(), Some(72));
+        let tf = &content.get()[3];
+        assert_eq!(tf.get_operands()[0].as_name(), Some(b"F1".as_slice()));
+    }
+    #[test]
+    fn parse_content_2() {
+        let input = b"[(A) -120 (B)] TJ % comment\n0.5 g 1 0 0 RG /P <</MCID 0>> BDC EMC";
+        let (_, content) = Content::parse(input).unwrap();
+        assert_eq!(content.get().len(), 5);
+        assert!(content.get()[0].get_operands()[0].as_array().is_some());
+        assert_eq!(content.get()[2].get_operator(), "RG");
+        assert_eq!(content.get()[2].get_operands().len(), 3);
+        assert!(content.get()[3].get_operands()[1].as_dict().is_some());
+    }
+    #[test]
+    fn parse_content_error() {
+        let input = b"BT (unterminated Tj ET";
+        let err = match Content::parse(input) {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.locate(input).get_offset(), Some(3));
+        assert!(Content::parse(b"0 0 m ) l").is_err());
+    }
+    #[test]
+    fn parse_content_unknown_operator() {
+        let input = b"BX 1 \xfe\xffop EX";
+        let (_, content) = Content::parse(input).unwrap();
+        assert_eq!(content.get()[1].get_operator_bytes(), b"\xfe\xffop");
+        assert_eq!(content.get()[1].get_operator(), "");
+        assert_eq!(content.encode(), b"BX\n1 \xfe\xffop\nEX\n");
+    }
+    #[test]
+    fn decode_content_streams() {
+        let mut info = Dictionary::new();
+        info.insert(Name::new(b"Filter"), Name::new(b"ASCIIHexDecode").into());
+        // "0 0 10 10" followed by "re f" in a second stream.
+        let first = Stream::new(info, b"302030203130203130>".to_vec()).unwrap();
+        let second = Stream::new(Dictionary::new(), b"re f".to_vec()).unwrap();
+        let content = Content::decode(&[&first, &second], &Limits::default()).unwrap();
+        assert_eq!(content.get().len(), 2);
+        assert_eq!(content.get()[0].get_operator(), "re");
+        assert_eq!(content.get()[0].get_operands().len(), 4);
+        assert_eq!(content.get()[1].get_operator(), "f");
+    }
+    #[test]
+    fn write_content_1() {
+        let input = b"q 1 0 0 1 72 720 cm BT /F1 12 Tf (Hi) Tj ET Q";
+        let (_, mut content) = Content::parse(input).unwrap();
+        content.get_mut()[1].get_operands_mut()[0] = Object::Real(Real::new(0.5));
+        content
+            .get_mut()
+            .insert(1, Operation::new("w", vec![2.into()]));
+        assert_eq!(
+            content.encode(),
+            b"q\n2 w\n0.5 0 0 1 72 720 cm\nBT\n/F1 12 Tf\n(Hi) Tj\nET\nQ\n"
+        );
+        let output = content.encode();
+        assert_eq!(Content::parse(&output).unwrap().1, content);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use super::write_inline_image;
+use crate::objects::Object;
+
+/// An operator of a content stream together with the operands preceding it. An inline image is
+/// a `BI` operation whose single operand is a stream holding the image dictionary and data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation<'b> {
+    operator: Cow<'b, [u8]>,
+    operands: Vec<Object<'b>>,
+}
+
+impl<'b> Operation<'b> {
+    pub fn new(operator: &'b str, operands: Vec<Object<'b>>) -> Self {
+        Self::from_bytes(operator.as_bytes(), operands)
+    }
+    /// Creates an operation whose operator need not be UTF-8, as unknown operators in a
+    /// `BX`/`EX` compatibility section may be any regular characters.
+    pub fn from_bytes(operator: &'b [u8], operands: Vec<Object<'b>>) -> Self {
+        Self {
+            operator: Cow::Borrowed(operator),
+            operands,
+        }
+    }
+    /// The operator as text, empty if it is not UTF-8 and so cannot be a known operator.
+    pub fn get_operator(&self) -> &str {
+        core::str::from_utf8(&self.operator).unwrap_or_default()
+    }
+    /// The operator exactly as it appeared in the content stream.
+    pub fn get_operator_bytes(&self) -> &[u8] {
+        &self.operator
+    }
+    pub fn get_operands(&self) -> &[Object<'b>] {
+        &self.operands
+    }
+    pub fn get_operands_mut(&mut self) -> &mut Vec<Object<'b>> {
+        &mut self.operands
+    }
+    pub fn into_owned(self) -> Operation<'static> {
+        Operation {
+            operator: Cow::Owned(self.operator.into_owned()),
+            operands: self.operands.into_iter().map(Object::into_owned).collect(),
+        }
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        if let (b"BI", [Object::Stream(image)]) = (&*self.operator, self.operands.as_slice()) {
+            return write_inline_image(image, w);
+        }
+        for operand in &self.operands {
+            operand.write_to(w)?;
+            w.write_all(b" ")?;
+        }
+        w.write_all(&self.operator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn write_operation_1() {
+        let op = Operation::new("Td", vec![Object::from(72), Object::from(-14.5)]);
+        let mut out = Vec::new();
+        op.write_to(&mut out).unwrap();
+        assert_eq!(out, b"72 -14.5 Td");
+        let mut out = Vec::new();
+        Operation::new("BT", vec![]).write_to(&mut out).unwrap();
+        assert_eq!(out, b"BT");
+    }
+}
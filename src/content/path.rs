@@ -0,0 +1,154 @@
+use crate::geometry::{Matrix, Rectangle};
+
+/// A segment of a path, with coordinates in the space the path was constructed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    /// A cubic Bézier curve through two control points to an end point.
+    CurveTo(f64, f64, f64, f64, f64, f64),
+    Close,
+}
+
+/// The rule deciding which regions a path encloses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+/// How a path is painted: stroked, filled or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paint {
+    pub stroke: bool,
+    pub fill: Option<FillRule>,
+}
+
+/// A path as built by the path construction operators.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+    /// The start of the last subpath, which closing it returns to.
+    start: Option<(f64, f64)>,
+    current: Option<(f64, f64)>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self) -> &[PathSegment] {
+        &self.segments
+    }
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self.start = Some((x, y));
+        self.current = self.start;
+    }
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.segments.push(PathSegment::LineTo(x, y));
+        self.current = Some((x, y));
+    }
+    pub fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) {
+        self.segments
+            .push(PathSegment::CurveTo(x1, y1, x2, y2, x3, y3));
+        self.current = Some((x3, y3));
+    }
+    pub fn close(&mut self) {
+        self.segments.push(PathSegment::Close);
+        self.current = self.start;
+    }
+    /// Appends a closed rectangle, as the `re` operator does.
+    pub fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.move_to(x, y);
+        self.line_to(x + width, y);
+        self.line_to(x + width, y + height);
+        self.line_to(x, y + height);
+        self.close();
+    }
+    /// The end point of the last segment, which a `v` curve starts from.
+    pub fn current_point(&self) -> Option<(f64, f64)> {
+        self.current
+    }
+    pub fn transform(&self, matrix: &Matrix) -> Path {
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| match *segment {
+                PathSegment::MoveTo(x, y) => {
+                    let (x, y) = matrix.transform_point(x, y);
+                    PathSegment::MoveTo(x, y)
+                }
+                PathSegment::LineTo(x, y) => {
+                    let (x, y) = matrix.transform_point(x, y);
+                    PathSegment::LineTo(x, y)
+                }
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    let (x1, y1) = matrix.transform_point(x1, y1);
+                    let (x2, y2) = matrix.transform_point(x2, y2);
+                    let (x3, y3) = matrix.transform_point(x3, y3);
+                    PathSegment::CurveTo(x1, y1, x2, y2, x3, y3)
+                }
+                PathSegment::Close => PathSegment::Close,
+            })
+            .collect();
+        let transform = |(x, y)| matrix.transform_point(x, y);
+        Path {
+            segments,
+            start: self.start.map(transform),
+            current: self.current.map(transform),
+        }
+    }
+    /// The smallest rectangle containing every point of the path, control points included.
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        let mut points = self.segments.iter().flat_map(|segment| match *segment {
+            PathSegment::MoveTo(x, y) | PathSegment::LineTo(x, y) => vec![(x, y)],
+            PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => vec![(x1, y1), (x2, y2), (x3, y3)],
+            PathSegment::Close => vec![],
+        });
+        let (x, y) = points.next()?;
+        let (llx, lly, urx, ury) = points.fold((x, y, x, y), |(llx, lly, urx, ury), (x, y)| {
+            (llx.min(x), lly.min(y), urx.max(x), ury.max(y))
+        });
+        Some(Rectangle::new(llx, lly, urx, ury))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn path_1() {
+        let mut path = Path::new();
+        assert_eq!(path.current_point(), None);
+        path.rectangle(10.0, 20.0, 30.0, 40.0);
+        assert_eq!(path.get().len(), 5);
+        assert_eq!(path.current_point(), Some((10.0, 20.0)));
+        path.curve_to(0.0, 0.0, 5.0, 5.0, 60.0, 0.0);
+        assert_eq!(path.current_point(), Some((60.0, 0.0)));
+        assert_eq!(
+            path.bounding_box(),
+            Some(Rectangle::new(0.0, 0.0, 60.0, 60.0))
+        );
+    }
+    #[test]
+    fn path_transform() {
+        let mut path = Path::new();
+        path.move_to(1.0, 2.0);
+        path.line_to(3.0, 4.0);
+        path.close();
+        let path = path.transform(&Matrix::translate(10.0, 20.0));
+        assert_eq!(path.current_point(), Some((11.0, 22.0)));
+        assert_eq!(
+            path.get(),
+            [
+                PathSegment::MoveTo(11.0, 22.0),
+                PathSegment::LineTo(13.0, 24.0),
+                PathSegment::Close
+            ]
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use super::{FillRule, Path};
+use crate::{
+    document::Document,
+    geometry::Matrix,
+    objects::{Array, Dictionary, Name, Object},
+};
+
+/// A color space selected by the color operators.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorSpace<'a, 'b> {
+    DeviceGray,
+    DeviceRgb,
+    DeviceCmyk,
+    Pattern,
+    /// A CIE-based or special color space given by its definition, such as `[/ICCBased 5 0 R]`.
+    Other {
+        definition: &'a Array<'b>,
+        components: usize,
+    },
+}
+
+impl<'a, 'b> ColorSpace<'a, 'b> {
+    /// Resolves a color space name or definition, returning `None` for unknown or invalid ones.
+    pub fn new(document: &'a Document<'b>, object: &'a Object<'b>) -> Option<Self> {
+        let object = document.resolve(object).ok()?;
+        if let Some(name) = object.as_name() {
+            return Self::from_name(name);
+        }
+        let definition = object.as_array()?;
+        let family = definition.get().first()?.as_name()?;
+        let parameter = || document.resolve(definition.get().get(1)?).ok();
+        let components = match family {
+            b"CalGray" | b"Indexed" | b"Separation" => 1,
+            b"CalRGB" | b"Lab" => 3,
+            b"ICCBased" => {
+                let info = parameter()?.as_stream()?.get_info();
+                match info.get(&Name::new(b"N")).and_then(Object::as_i64) {
+                    Some(n @ (1 | 3 | 4)) => usize::try_from(n).ok()?,
+                    // Only a device alternate is followed, as another ICC profile could lead
+                    // back to this one.
+                    _ => {
+                        let alternate = info.get(&Name::new(b"Alternate"))?;
+                        return Self::from_name(document.resolve(alternate).ok()?.as_name()?);
+                    }
+                }
+            }
+            b"DeviceN" => parameter()?.as_array()?.len(),
+            _ => return Self::from_name(family),
+        };
+        Some(ColorSpace::Other {
+            definition,
+            components,
+        })
+    }
+    /// The color space of a device or pattern family name, including the abbreviations used by
+    /// inline images.
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"DeviceGray" | b"G" => Some(ColorSpace::DeviceGray),
+            b"DeviceRGB" | b"RGB" => Some(ColorSpace::DeviceRgb),
+            b"DeviceCMYK" | b"CMYK" => Some(ColorSpace::DeviceCmyk),
+            b"Pattern" => Some(ColorSpace::Pattern),
+            _ => None,
+        }
+    }
+    /// The number of color components, which is 0 for patterns.
+    pub fn components(&self) -> usize {
+        match self {
+            ColorSpace::DeviceGray => 1,
+            ColorSpace::DeviceRgb => 3,
+            ColorSpace::DeviceCmyk => 4,
+            ColorSpace::Pattern => 0,
+            ColorSpace::Other { components, .. } => *components,
+        }
+    }
+    /// The color a color space starts with when it is selected.
+    pub fn initial_color(&self) -> Color {
+        let components = match self {
+            ColorSpace::DeviceCmyk => vec![0.0, 0.0, 0.0, 1.0],
+            ColorSpace::Other {
+                definition,
+                components,
+            } if matches!(
+                definition.get().first().and_then(Object::as_name),
+                Some(b"Separation" | b"DeviceN")
+            ) =>
+            {
+                vec![1.0; *components]
+            }
+            _ => vec![0.0; self.components()],
+        };
+        Color::new(components)
+    }
+}
+
+/// A color as set by the color operators, with the pattern name for pattern color spaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Color {
+    components: Vec<f64>,
+    pattern: Option<Name<'static>>,
+}
+
+impl Color {
+    pub fn new(components: Vec<f64>) -> Self {
+        Self {
+            components,
+            pattern: None,
+        }
+    }
+    pub fn with_pattern(components: Vec<f64>, pattern: Name<'static>) -> Self {
+        Self {
+            components,
+            pattern: Some(pattern),
+        }
+    }
+    pub fn get_components(&self) -> &[f64] {
+        &self.components
+    }
+    pub fn get_pattern(&self) -> Option<&Name<'static>> {
+        self.pattern.as_ref()
+    }
+}
+
+/// The text state parameters. The text matrix is not part of the graphics state and is passed
+/// along with each run of text instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextState<'a, 'b> {
+    pub char_spacing: f64,
+    pub word_spacing: f64,
+    /// The horizontal scaling as a factor, `Tz` giving it in percent.
+    pub horizontal_scaling: f64,
+    pub leading: f64,
+    pub font: Option<&'a Dictionary<'b>>,
+    pub font_size: f64,
+    pub render_mode: i64,
+    pub rise: f64,
+    pub knockout: bool,
+}
+
+impl Default for TextState<'_, '_> {
+    fn default() -> Self {
+        Self {
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: 1.0,
+            leading: 0.0,
+            font: None,
+            font_size: 0.0,
+            render_mode: 0,
+            rise: 0.0,
+            knockout: true,
+        }
+    }
+}
+
+/// A clipping path, transformed by the CTM in effect when it was set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    pub path: Path,
+    pub rule: FillRule,
+}
+
+/// The parameters saved by `q` and restored by `Q`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphicsState<'a, 'b> {
+    pub ctm: Matrix,
+    /// The clipping paths intersected so far, the region being the intersection of all of them.
+    pub clip: Vec<Clip>,
+    pub stroke_color_space: ColorSpace<'a, 'b>,
+    pub fill_color_space: ColorSpace<'a, 'b>,
+    pub stroke_color: Color,
+    pub fill_color: Color,
+    pub line_width: f64,
+    pub line_cap: i64,
+    pub line_join: i64,
+    pub miter_limit: f64,
+    pub dash_array: Vec<f64>,
+    pub dash_phase: f64,
+    pub rendering_intent: Name<'static>,
+    pub flatness: f64,
+    pub stroke_alpha: f64,
+    pub fill_alpha: f64,
+    pub blend_mode: Name<'static>,
+    /// The `/SMask` of the last ExtGState that set one, `None` standing for no soft mask.
+    pub soft_mask: Option<&'a Dictionary<'b>>,
+    pub alpha_is_shape: bool,
+    pub text: TextState<'a, 'b>,
+}
+
+impl<'a, 'b> GraphicsState<'a, 'b> {
+    pub fn new(ctm: Matrix) -> Self {
+        Self {
+            ctm,
+            clip: Vec::new(),
+            stroke_color_space: ColorSpace::DeviceGray,
+            fill_color_space: ColorSpace::DeviceGray,
+            stroke_color: ColorSpace::DeviceGray.initial_color(),
+            fill_color: ColorSpace::DeviceGray.initial_color(),
+            line_width: 1.0,
+            line_cap: 0,
+            line_join: 0,
+            miter_limit: 10.0,
+            dash_array: Vec::new(),
+            dash_phase: 0.0,
+            rendering_intent: Name::new(b"RelativeColorimetric"),
+            flatness: 1.0,
+            stroke_alpha: 1.0,
+            fill_alpha: 1.0,
+            blend_mode: Name::new(b"Normal"),
+            soft_mask: None,
+            alpha_is_shape: false,
+            text: TextState::default(),
+        }
+    }
+    /// Applies the parameters of an ExtGState dictionary, ignoring invalid ones.
+    pub fn apply(&mut self, document: &'a Document<'b>, ext_g_state: &'a Dictionary<'b>) {
+        for (key, value) in ext_g_state.iter() {
+            let Ok(value) = document.resolve(value) else {
+                continue;
+            };
+            match key.get() {
+                b"LW" => set(&mut self.line_width, value.as_f64()),
+                b"LC" => set(&mut self.line_cap, value.as_i64()),
+                b"LJ" => set(&mut self.line_join, value.as_i64()),
+                b"ML" => set(&mut self.miter_limit, value.as_f64()),
+                b"D" => {
+                    if let Some([array, phase]) = value.as_array().map(Array::get)
+                        && let Some((array, phase)) = dash(array).zip(phase.as_f64())
+                    {
+                        self.dash_array = array;
+                        self.dash_phase = phase;
+                    }
+                }
+                b"RI" => {
+                    if let Some(intent) = value.as_name() {
+                        self.rendering_intent = Name::new(intent).into_owned();
+                    }
+                }
+                b"FL" => set(&mut self.flatness, value.as_f64()),
+                b"Font" => {
+                    if let Some([font, size]) = value.as_array().map(Array::get) {
+                        let font = document.resolve(font).ok().and_then(Object::as_dict);
+                        if let Some((font, size)) = font.zip(size.as_f64()) {
+                            self.text.font = Some(font);
+                            self.text.font_size = size;
+                        }
+                    }
+                }
+                b"CA" => set(&mut self.stroke_alpha, value.as_f64()),
+                b"ca" => set(&mut self.fill_alpha, value.as_f64()),
+                b"BM" => {
+                    // An array lists blend modes in order of preference; the first is standard.
+                    let mode = match value.as_array() {
+                        Some(modes) => modes.get().first().and_then(Object::as_name),
+                        None => value.as_name(),
+                    };
+                    if let Some(mode) = mode {
+                        self.blend_mode = Name::new(mode).into_owned();
+                    }
+                }
+                b"SMask" => {
+                    if value.as_name().is_some() {
+                        self.soft_mask = None;
+                    } else if let Some(mask) = value.as_dict() {
+                        self.soft_mask = Some(mask);
+                    }
+                }
+                b"AIS" => set(&mut self.alpha_is_shape, value.as_bool()),
+                b"TK" => set(&mut self.text.knockout, value.as_bool()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Assigns `value` to `field` unless it is missing or invalid, which leaves the field unchanged.
+pub(super) fn set<T>(field: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
+
+pub(super) fn dash(array: &Object) -> Option<Vec<f64>> {
+    array.as_array()?.get().iter().map(Object::as_f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::tests::build_pdf, objects::ObjectId};
+    #[test]
+    fn color_space_1() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "[/ICCBased 3 0 R]",
+                "<</N 4 /Length 0>>stream\n\nendstream",
+                "[/Separation /Spot /DeviceCMYK 5 0 R]",
+                "<</FunctionType 2 /Domain [0 1] /N 1>>",
+            ],
+            "<</Size 6 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let icc = Object::from(ObjectId::new(2, 0));
+        let icc = ColorSpace::new(&document, &icc).unwrap();
+        assert_eq!(icc.components(), 4);
+        assert_eq!(icc.initial_color().get_components(), [0.0; 4]);
+        let separation = Object::from(ObjectId::new(4, 0));
+        let separation = ColorSpace::new(&document, &separation).unwrap();
+        assert_eq!(separation.initial_color().get_components(), [1.0]);
+        let cmyk = Object::from(Name::new(b"DeviceCMYK"));
+        let cmyk = ColorSpace::new(&document, &cmyk).unwrap();
+        assert_eq!(cmyk, ColorSpace::DeviceCmyk);
+        assert_eq!(cmyk.initial_color().get_components(), [0.0, 0.0, 0.0, 1.0]);
+        let unknown = Object::from(Name::new(b"CS0"));
+        assert_eq!(ColorSpace::new(&document, &unknown), None);
+    }
+    #[test]
+    fn color_space_invalid_icc() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "[/ICCBased 3 0 R]",
+                "<</N 1000000000000000 /Alternate /DeviceRGB /Length 0>>stream\n\nendstream",
+                "[/ICCBased 5 0 R]",
+                "<</N 2 /Length 0>>stream\n\nendstream",
+            ],
+            "<</Size 6 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let alternate = Object::from(ObjectId::new(2, 0));
+        assert_eq!(
+            ColorSpace::new(&document, &alternate),
+            Some(ColorSpace::DeviceRgb)
+        );
+        let invalid = Object::from(ObjectId::new(4, 0));
+        assert_eq!(ColorSpace::new(&document, &invalid), None);
+    }
+}
@@ -0,0 +1,298 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod rc4;
+mod standard;
+pub use rc4::*;
+pub use standard::*;
+
+use std::borrow::Cow;
+
+use md5::{Digest, Md5};
+
+use crate::{
+    error::{Error, Result},
+    objects::{Dictionary, GetObj, Name, Object, ObjectId, String},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptMethod {
+    Identity,
+    Rc4,
+    AesV2,
+    AesV3,
+}
+
+impl CryptMethod {
+    fn from_filter(encrypt: &Dictionary<'_>, version: i32, key: &'static [u8]) -> Result<Self> {
+        if version < 4 {
+            return Ok(Self::Rc4);
+        }
+        let Some(filter) = encrypt.get(&Name::new(key)) else {
+            return Ok(Self::Identity);
+        };
+        let filter: &Name = filter
+            .get_obj()
+            .ok_or(Error::UnsupportedEncryption("crypt filter is not a name"))?;
+        if filter.get() == b"Identity" {
+            return Ok(Self::Identity);
+        }
+        let crypt_filter =
+            get_dictionary(encrypt, &Name::new(b"CF")).and_then(|cf| get_dictionary(cf, filter));
+        let method: Option<&Name> = crypt_filter
+            .and_then(|f| f.get(&Name::new(b"CFM")))
+            .and_then(|m| m.get_obj());
+        match method.map(Name::get) {
+            None | Some(b"None") => Ok(Self::Identity),
+            Some(b"V2") => Ok(Self::Rc4),
+            Some(b"AESV2") => Ok(Self::AesV2),
+            Some(b"AESV3") => Ok(Self::AesV3),
+            Some(_) => Err(Error::UnsupportedEncryption(
+                "unsupported crypt filter method",
+            )),
+        }
+    }
+}
+
+fn get_dictionary<'a, 'b>(
+    dictionary: &'a Dictionary<'b>,
+    key: &Name<'b>,
+) -> Option<&'a Dictionary<'b>> {
+    dictionary.get(key)?.get_obj()
+}
+
+/// Decrypts the strings and streams of a document protected by the standard security handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityHandler {
+    key: Vec<u8>,
+    string_method: CryptMethod,
+    stream_method: CryptMethod,
+    encrypt_metadata: bool,
+}
+
+impl SecurityHandler {
+    /// Authenticates `password` as either the user or the owner password. `id` is the first
+    /// element of the trailer's `/ID` array.
+    pub fn new(encrypt: &Dictionary<'_>, id: &[u8], password: &[u8]) -> Result<Self> {
+        let standard = StandardSecurity::new(encrypt)?;
+        let version = standard.get_version();
+        Ok(Self {
+            key: standard.authenticate(password, id)?,
+            string_method: CryptMethod::from_filter(encrypt, version, b"StrF")?,
+            stream_method: CryptMethod::from_filter(encrypt, version, b"StmF")?,
+            encrypt_metadata: standard.get_encrypt_metadata(),
+        })
+    }
+    pub fn get_key(&self) -> &[u8] {
+        &self.key
+    }
+    pub fn get_string_method(&self) -> CryptMethod {
+        self.string_method
+    }
+    pub fn get_stream_method(&self) -> CryptMethod {
+        self.stream_method
+    }
+    /// Decrypts every string and stream inside the object `id` in place.
+    pub fn decrypt(&self, id: ObjectId, object: &mut Object<'_>) -> Result<()> {
+        match object {
+            Object::String(s) => {
+                *s = String::new(self.decrypt_bytes(id, self.string_method, s.get())?);
+            }
+            Object::Array(a) => {
+                for o in a.get_mut() {
+                    self.decrypt(id, o)?;
+                }
+            }
+            Object::Dictionary(d) => self.decrypt_dictionary(id, d)?,
+            Object::Stream(s) => {
+                let kind: Option<&Name> = s.info.get(&Name::new(b"Type")).and_then(|t| t.get_obj());
+                match kind.map(Name::get) {
+                    Some(b"XRef") => return Ok(()),
+                    Some(b"Metadata") if !self.encrypt_metadata => return Ok(()),
+                    _ => {}
+                }
+                self.decrypt_dictionary(id, &mut s.info)?;
+                let data = self.decrypt_bytes(id, self.stream_method, s.get_data())?;
+                s.data = Cow::Owned(data);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn decrypt_dictionary(&self, id: ObjectId, dictionary: &mut Dictionary<'_>) -> Result<()> {
+        for o in dictionary.values_mut() {
+            self.decrypt(id, o)?;
+        }
+        Ok(())
+    }
+    fn decrypt_bytes(&self, id: ObjectId, method: CryptMethod, data: &[u8]) -> Result<Vec<u8>> {
+        match method {
+            CryptMethod::Identity => Ok(data.to_vec()),
+            CryptMethod::Rc4 => Ok(rc4(&self.object_key(id, false), data)),
+            CryptMethod::AesV2 => aes_decrypt(&self.object_key(id, true), data),
+            CryptMethod::AesV3 => aes_decrypt(&self.key, data),
+        }
+    }
+    /// Algorithm 1: derives the key of a single object from the file key.
+    fn object_key(&self, id: ObjectId, aes: bool) -> Vec<u8> {
+        let mut md5 = Md5::new();
+        md5.update(&self.key);
+        md5.update(id.number().to_le_bytes().get(..3).unwrap_or_default());
+        md5.update(id.generation().to_le_bytes());
+        if aes {
+            md5.update(b"sAlT");
+        }
+        let mut key = md5.finalize().to_vec();
+        key.truncate(self.key.len().saturating_add(5));
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Stream;
+
+    const ID: &str = "0123456789abcdeffedcba9876543210";
+
+    fn handler(encrypt: &str, password: &[u8]) -> Result<SecurityHandler> {
+        let (_, encrypt) = Dictionary::parse(encrypt.as_bytes()).unwrap();
+        SecurityHandler::new(&encrypt, &hex::decode(ID).unwrap(), password)
+    }
+    fn decrypt_string(handler: &SecurityHandler, number: u32, data: &str) -> Vec<u8> {
+        let mut object = Object::String(String::new(hex::decode(data).unwrap()));
+        handler
+            .decrypt(ObjectId::new(number, 0), &mut object)
+            .unwrap();
+        let string: &String = object.get_obj().unwrap();
+        string.get().to_vec()
+    }
+    fn decrypt_stream(handler: &SecurityHandler, number: u32, data: &str) -> Vec<u8> {
+        let data = hex::decode(data).unwrap();
+        let input = [
+            format!("<< /Length {} >>\nstream\n", data.len()).as_bytes(),
+            &data,
+            b"\nendstream",
+        ]
+        .concat();
+        let (_, stream) = Stream::parse(&input).unwrap();
+        let mut object = Object::Stream(stream);
+        handler
+            .decrypt(ObjectId::new(number, 0), &mut object)
+            .unwrap();
+        let stream: &Stream = object.get_obj().unwrap();
+        stream.get_data().to_vec()
+    }
+
+    const R2: &str = "<< /Filter /Standard /V 1 /R 2 /P -3904 \
+        /O <2055c756c72e1ad702608e8196acad447ad32d17cff583235f6dd15fed7dab67> \
+        /U <2c0e1bde1393bc5656ac190247f627440bcb7f76493969d268610771db5b44e9> >>";
+    const R3: &str = "<< /Filter /Standard /V 2 /R 3 /Length 128 /P -3904 \
+        /O <0ba3835f88f90388e74e54584125ce142be0de24c6b0d37746e075b891756671> \
+        /U <c9bbe2a3956ec521581a87749e0bc91a00000000000000000000000000000000> >>";
+    const R4: &str = "<< /Filter /Standard /V 4 /R 4 /Length 128 /P -3904 \
+        /CF << /StdCF << /CFM /AESV2 /Length 16 >> >> /StmF /StdCF /StrF /StdCF \
+        /O <566fa873ee33c797cd3b904fdadf814afa34df9a38f6ed41b984e2c6da2aa6f5> \
+        /U <48677900f3bbb0acad56486ed7d3be6e00000000000000000000000000000000> >>";
+    const R6: &str = "<< /Filter /Standard /V 5 /R 6 /Length 256 /P -3904 \
+        /CF << /StdCF << /CFM /AESV3 /Length 32 >> >> /StmF /StdCF /StrF /StdCF \
+        /O <857002dd54e1efa185da531afbdddae14135cfa2becb1389d37f265d1e3bf5b71112131415161718191a1b1c1d1e1f20> \
+        /U <17424b40ead366f7ddef0ff073608aa68ba701714b5cef3409b94c4ffa7637260102030405060708090a0b0c0d0e0f10> \
+        /OE <ffa46a897c09bb541d28b7fe1f5d3bb6fe049311483a5de5cfa3c396ed13384b> \
+        /UE <08ad44ecab3121f78e8f57c3ae6bd405667e8983ec0eb65506310f3085c9ccde> >>";
+
+    #[test]
+    fn decrypt_r2() {
+        let handler = handler(R2, b"").unwrap();
+        assert_eq!(handler.get_key().len(), 5);
+        assert_eq!(
+            decrypt_string(&handler, 2, "35e492c16fa7593d6f94da"),
+            b"Secret text"
+        );
+    }
+    #[test]
+    fn decrypt_r3_user() {
+        let handler = handler(R3, b"user").unwrap();
+        assert_eq!(handler.get_string_method(), CryptMethod::Rc4);
+        assert_eq!(
+            decrypt_string(&handler, 2, "7b6f18d61fe2d0724591b4"),
+            b"Secret text"
+        );
+        assert_eq!(
+            decrypt_stream(&handler, 3, "6c08937251bdc9576662b3e7c01fd3"),
+            b"BT /F1 12 Tf ET"
+        );
+    }
+    #[test]
+    fn decrypt_r3_owner() {
+        let user = handler(R3, b"user").unwrap();
+        let owner = handler(R3, b"owner").unwrap();
+        assert_eq!(user.get_key(), owner.get_key());
+    }
+    #[test]
+    fn decrypt_r3_wrong_password() {
+        assert_eq!(handler(R3, b""), Err(Error::IncorrectPassword));
+        assert_eq!(handler(R3, b"User"), Err(Error::IncorrectPassword));
+    }
+    #[test]
+    fn decrypt_r4() {
+        let handler = handler(R4, b"").unwrap();
+        assert_eq!(handler.get_stream_method(), CryptMethod::AesV2);
+        assert_eq!(
+            decrypt_string(
+                &handler,
+                2,
+                "000102030405060708090a0b0c0d0e0f6e201808afa2e7d6b6a638255b379832"
+            ),
+            b"Secret text"
+        );
+        assert_eq!(
+            decrypt_stream(
+                &handler,
+                3,
+                "000102030405060708090a0b0c0d0e0fe7d6b16c9e1f2c9b72b5d3b3d5fa87b4"
+            ),
+            b"BT /F1 12 Tf ET"
+        );
+        assert_eq!(
+            handler.get_key(),
+            self::handler(R4, b"owner").unwrap().get_key()
+        );
+    }
+    #[test]
+    fn decrypt_r6() {
+        let user = handler(R6, b"user").unwrap();
+        let key: Vec<u8> = (100..132).collect();
+        assert_eq!(user.get_key(), key);
+        assert_eq!(
+            decrypt_string(
+                &user,
+                2,
+                "000102030405060708090a0b0c0d0e0f008207b353810c606e16b366e03d5fba"
+            ),
+            b"Secret text"
+        );
+        assert_eq!(
+            decrypt_stream(
+                &user,
+                3,
+                "000102030405060708090a0b0c0d0e0f83f2e085094db4b40e8d541e0245774c"
+            ),
+            b"BT /F1 12 Tf ET"
+        );
+        let owner = handler(R6, b"owner").unwrap();
+        assert_eq!(owner.get_key(), key);
+        assert_eq!(handler(R6, b""), Err(Error::IncorrectPassword));
+    }
+    #[test]
+    fn decrypt_unsupported_filter() {
+        assert_eq!(
+            handler("<< /Filter /Adobe.PubSec /V 4 /R 4 >>", b""),
+            Err(Error::UnsupportedEncryption(
+                "only the standard security handler is supported"
+            ))
+        );
+    }
+}
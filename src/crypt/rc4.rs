@@ -0,0 +1,43 @@
+pub fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: [u8; 256] = core::array::from_fn(|i| u8::try_from(i).unwrap_or(0));
+    let get = |state: &[u8; 256], i: u8| state.get(usize::from(i)).copied().unwrap_or(0);
+    if !key.is_empty() {
+        let mut j = 0u8;
+        for (i, k) in (0..=u8::MAX).zip(key.iter().cycle()) {
+            j = j.wrapping_add(get(&state, i)).wrapping_add(*k);
+            state.swap(usize::from(i), usize::from(j));
+        }
+    }
+    let mut i = 0u8;
+    let mut j = 0u8;
+    data.iter()
+        .map(|byte| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(get(&state, i));
+            state.swap(usize::from(i), usize::from(j));
+            let k = get(&state, get(&state, i).wrapping_add(get(&state, j)));
+            byte ^ k
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn rc4_1() {
+        assert_eq!(
+            rc4(b"Key", b"Plaintext"),
+            [0xbb, 0xf3, 0x16, 0xe8, 0xd9, 0x40, 0xaf, 0x0a, 0xd3]
+        );
+    }
+    #[test]
+    fn rc4_2() {
+        assert_eq!(
+            rc4(b"Secret", b"Attack at dawn"),
+            [
+                0x45, 0xa0, 0x1f, 0x64, 0x5f, 0xc3, 0x5b, 0x38, 0x35, 0x52, 0x54, 0x4b, 0x9b, 0xf5
+            ]
+        );
+    }
+}
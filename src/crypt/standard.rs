@@ -0,0 +1,290 @@
+use cbc::cipher::{
+    BlockDecryptMut, BlockEncryptMut, KeyIvInit,
+    block_padding::{NoPadding, Pkcs7},
+};
+use md5::{Digest, Md5};
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::rc4;
+use crate::{
+    error::{Error, Result},
+    objects::{Boolean, Dictionary, GetObj, Integer, Name, String},
+};
+
+const PADDING: [u8; 32] = [
+    0x28, 0xbf, 0x4e, 0x5e, 0x4e, 0x75, 0x8a, 0x41, 0x64, 0x00, 0x4e, 0x56, 0xff, 0xfa, 0x01, 0x08,
+    0x2e, 0x2e, 0x00, 0xb6, 0xd0, 0x68, 0x3e, 0x80, 0x2f, 0x0c, 0xa9, 0xfe, 0x64, 0x53, 0x69, 0x7a,
+];
+
+/// The entries of a `/Filter /Standard` encryption dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandardSecurity<'a> {
+    version: i32,
+    revision: i32,
+    key_length: usize,
+    owner: &'a [u8],
+    user: &'a [u8],
+    owner_encryption: &'a [u8],
+    user_encryption: &'a [u8],
+    permissions: i32,
+    encrypt_metadata: bool,
+}
+
+impl<'a> StandardSecurity<'a> {
+    pub fn new(encrypt: &'a Dictionary<'_>) -> Result<Self> {
+        let filter: Option<&Name> = encrypt.get(&Name::new(b"Filter")).and_then(|f| f.get_obj());
+        if filter.is_none_or(|f| f.get() != b"Standard") {
+            return Err(Error::UnsupportedEncryption(
+                "only the standard security handler is supported",
+            ));
+        }
+        let version = get_integer(encrypt, b"V")?.unwrap_or(0);
+        let revision =
+            get_integer(encrypt, b"R")?.ok_or(Error::UnsupportedEncryption("missing /R"))?;
+        let key_length = match revision {
+            2 => 5,
+            3 | 4 => {
+                let bits = get_integer(encrypt, b"Length")?.unwrap_or(40);
+                usize::try_from(bits)
+                    .ok()
+                    .filter(|bits| (40..=128).contains(bits) && bits % 8 == 0)
+                    .ok_or(Error::UnsupportedEncryption("invalid key length"))?
+                    >> 3
+            }
+            5 | 6 => 32,
+            _ => return Err(Error::UnsupportedEncryption("unsupported revision")),
+        };
+        let encrypt_metadata: Option<&Boolean> = encrypt
+            .get(&Name::new(b"EncryptMetadata"))
+            .and_then(|e| e.get_obj());
+        Ok(Self {
+            version,
+            revision,
+            key_length,
+            owner: get_string(encrypt, b"O")?,
+            user: get_string(encrypt, b"U")?,
+            owner_encryption: get_string(encrypt, b"OE").unwrap_or_default(),
+            user_encryption: get_string(encrypt, b"UE").unwrap_or_default(),
+            permissions: get_integer(encrypt, b"P")?
+                .ok_or(Error::UnsupportedEncryption("missing /P"))?,
+            encrypt_metadata: encrypt_metadata.is_none_or(Boolean::get),
+        })
+    }
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+    pub fn get_revision(&self) -> i32 {
+        self.revision
+    }
+    pub fn get_permissions(&self) -> i32 {
+        self.permissions
+    }
+    pub fn get_encrypt_metadata(&self) -> bool {
+        self.encrypt_metadata
+    }
+    pub fn get_key_length(&self) -> usize {
+        self.key_length
+    }
+    /// Authenticates `password` as the user or the owner password and returns the file key.
+    pub fn authenticate(&self, password: &[u8], id: &[u8]) -> Result<Vec<u8>> {
+        let key = if self.revision >= 5 {
+            self.authenticate_user_r6(password)?
+                .or(self.authenticate_owner_r6(password)?)
+        } else {
+            self.authenticate_user(&pad_password(password), id)
+                .or_else(|| self.authenticate_owner(password, id))
+        };
+        key.ok_or(Error::IncorrectPassword)
+    }
+    /// Algorithm 2: computes the file key from a padded user password.
+    fn compute_key(&self, padded: &[u8; 32], id: &[u8]) -> Vec<u8> {
+        let mut md5 = Md5::new();
+        md5.update(padded);
+        md5.update(self.owner.get(..32).unwrap_or(self.owner));
+        md5.update(self.permissions.to_le_bytes());
+        md5.update(id);
+        if self.revision >= 4 && !self.encrypt_metadata {
+            md5.update([0xff; 4]);
+        }
+        let mut hash = md5.finalize().to_vec();
+        if self.revision >= 3 {
+            for _ in 0..50 {
+                hash = Md5::digest(hash.get(..self.key_length).unwrap_or(&hash)).to_vec();
+            }
+        }
+        hash.truncate(self.key_length);
+        hash
+    }
+    /// Algorithms 4 to 6: returns the file key if the padded password is the user password.
+    fn authenticate_user(&self, padded: &[u8; 32], id: &[u8]) -> Option<Vec<u8>> {
+        let key = self.compute_key(padded, id);
+        let (expected, length) = if self.revision == 2 {
+            (rc4(&key, &PADDING), 32)
+        } else {
+            let mut md5 = Md5::new();
+            md5.update(PADDING);
+            md5.update(id);
+            let mut hash = rc4(&key, &md5.finalize());
+            for i in 1..=19u8 {
+                hash = rc4(&xor_key(&key, i), &hash);
+            }
+            (hash, 16)
+        };
+        (self.user.get(..length)? == expected.get(..length)?).then_some(key)
+    }
+    /// Algorithm 7: recovers the user password from `/O` and authenticates with it.
+    fn authenticate_owner(&self, password: &[u8], id: &[u8]) -> Option<Vec<u8>> {
+        let mut hash = Md5::digest(pad_password(password)).to_vec();
+        if self.revision >= 3 {
+            for _ in 0..50 {
+                hash = Md5::digest(&hash).to_vec();
+            }
+        }
+        hash.truncate(self.key_length);
+        let mut user = self.owner.get(..32)?.to_vec();
+        if self.revision == 2 {
+            user = rc4(&hash, &user);
+        } else {
+            for i in (0..=19u8).rev() {
+                user = rc4(&xor_key(&hash, i), &user);
+            }
+        }
+        self.authenticate_user(&user.try_into().ok()?, id)
+    }
+    /// Algorithm 2.A for the user password of revision 5 and 6 files.
+    fn authenticate_user_r6(&self, password: &[u8]) -> Result<Option<Vec<u8>>> {
+        let password = password.get(..127).unwrap_or(password);
+        let (hash, validation_salt, key_salt) = split_salts(self.user)?;
+        if self.hash(password, validation_salt, &[])? != hash {
+            return Ok(None);
+        }
+        let key = self.hash(password, key_salt, &[])?;
+        decrypt_file_key(&key, self.user_encryption).map(Some)
+    }
+    /// Algorithm 2.A for the owner password of revision 5 and 6 files.
+    fn authenticate_owner_r6(&self, password: &[u8]) -> Result<Option<Vec<u8>>> {
+        let password = password.get(..127).unwrap_or(password);
+        let (hash, validation_salt, key_salt) = split_salts(self.owner)?;
+        let user = self
+            .user
+            .get(..48)
+            .ok_or(Error::UnsupportedEncryption("invalid /U"))?;
+        if self.hash(password, validation_salt, user)? != hash {
+            return Ok(None);
+        }
+        let key = self.hash(password, key_salt, user)?;
+        decrypt_file_key(&key, self.owner_encryption).map(Some)
+    }
+    /// Algorithm 2.B, or plain SHA-256 for the deprecated revision 5.
+    fn hash(&self, password: &[u8], salt: &[u8], user: &[u8]) -> Result<Vec<u8>> {
+        let mut hash = Sha256::new()
+            .chain_update(password)
+            .chain_update(salt)
+            .chain_update(user)
+            .finalize()
+            .to_vec();
+        if self.revision == 5 {
+            return Ok(hash);
+        }
+        for round in 0u32.. {
+            let block: Vec<u8> = [password, &hash, user].concat();
+            let mut data = block.repeat(64);
+            let length = data.len();
+            let (key, iv) = hash
+                .split_at_checked(16)
+                .ok_or(Error::Decrypt("invalid hash"))?;
+            let iv = iv.get(..16).ok_or(Error::Decrypt("invalid hash"))?;
+            let encrypted = cbc::Encryptor::<aes::Aes128>::new_from_slices(key, iv)
+                .map_err(|_| Error::Decrypt("invalid hash"))?
+                .encrypt_padded_mut::<NoPadding>(&mut data, length)
+                .map_err(|_| Error::Decrypt("invalid hash"))?;
+            let sum: u32 = encrypted.iter().take(16).map(|b| u32::from(*b)).sum();
+            hash = match sum % 3 {
+                0 => Sha256::digest(encrypted).to_vec(),
+                1 => Sha384::digest(encrypted).to_vec(),
+                _ => Sha512::digest(encrypted).to_vec(),
+            };
+            let last = encrypted.last().copied().map(u32::from).unwrap_or(0);
+            if round >= 63 && last.saturating_add(31) <= round {
+                break;
+            }
+        }
+        hash.truncate(32);
+        Ok(hash)
+    }
+}
+
+/// Decrypts a string or stream with the AES-CBC variant matching the key length, the first 16
+/// bytes of the data being the initialization vector.
+pub fn aes_decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let Some((iv, data)) = data.split_at_checked(16) else {
+        return Ok(Vec::new());
+    };
+    let mut buffer = data.to_vec();
+    let invalid = |_| Error::Decrypt("invalid AES key");
+    let unpad = |_| Error::Decrypt("invalid AES padding");
+    let decrypted = match key.len() {
+        16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(key, iv)
+            .map_err(invalid)?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map_err(unpad)?,
+        _ => cbc::Decryptor::<aes::Aes256>::new_from_slices(key, iv)
+            .map_err(invalid)?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map_err(unpad)?,
+    };
+    Ok(decrypted.to_vec())
+}
+
+fn decrypt_file_key(key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>> {
+    let mut buffer = encrypted
+        .get(..32)
+        .ok_or(Error::UnsupportedEncryption("invalid /OE or /UE"))?
+        .to_vec();
+    let key = cbc::Decryptor::<aes::Aes256>::new_from_slices(key, &[0; 16])
+        .map_err(|_| Error::Decrypt("invalid AES key"))?
+        .decrypt_padded_mut::<NoPadding>(&mut buffer)
+        .map_err(|_| Error::Decrypt("invalid AES padding"))?;
+    Ok(key.to_vec())
+}
+
+fn split_salts(value: &[u8]) -> Result<(&[u8], &[u8], &[u8])> {
+    let invalid = Error::UnsupportedEncryption("invalid /O or /U");
+    let (hash, salts) = value.split_at_checked(32).ok_or(invalid.clone())?;
+    let (validation_salt, key_salt) = salts.split_at_checked(8).ok_or(invalid.clone())?;
+    Ok((hash, validation_salt, key_salt.get(..8).ok_or(invalid)?))
+}
+
+fn pad_password(password: &[u8]) -> [u8; 32] {
+    let mut padded = PADDING;
+    for (p, c) in padded.iter_mut().zip(password.iter().chain(PADDING.iter())) {
+        *p = *c;
+    }
+    padded
+}
+
+fn xor_key(key: &[u8], value: u8) -> Vec<u8> {
+    key.iter().map(|k| k ^ value).collect()
+}
+
+fn get_integer(dict: &Dictionary<'_>, key: &'static [u8]) -> Result<Option<i32>> {
+    let Some(value) = dict.get(&Name::new(key)) else {
+        return Ok(None);
+    };
+    let value: &Integer = value.get_obj().ok_or(Error::UnsupportedEncryption(
+        "encryption entry is not an integer",
+    ))?;
+    // `/P` is a 32-bit mask that some writers store as an unsigned number.
+    let value = i32::try_from(value.get())
+        .or_else(|_| u32::try_from(value.get()).map(u32::cast_signed))
+        .map_err(|_| Error::UnsupportedEncryption("encryption entry out of range"))?;
+    Ok(Some(value))
+}
+
+fn get_string<'a>(dict: &'a Dictionary<'_>, key: &'static [u8]) -> Result<&'a [u8]> {
+    let value: &String = dict
+        .get(&Name::new(key))
+        .and_then(|v| v.get_obj())
+        .ok_or(Error::UnsupportedEncryption("missing /O or /U"))?;
+    Ok(value.get())
+}
@@ -0,0 +1,603 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod page;
+mod recovery;
+mod version;
+use std::{
+    cell::OnceCell,
+    collections::{BTreeMap, BTreeSet},
+};
+
+pub use page::*;
+pub use recovery::*;
+pub use version::*;
+
+use crate::{
+    crypt::SecurityHandler,
+    error::{Error, Result},
+    limits::Limits,
+    objects::{Dictionary, GetObj, IndirectObject, Integer, Object, ObjectId, Stream},
+    xref::{ObjectStream, StartXref, Trailer, XrefEntry, XrefSection, XrefStream, XrefTable},
+};
+
+/// A PDF file whose objects are parsed on first access and cached afterwards.
+#[derive(Debug)]
+pub struct Document<'b> {
+    input: &'b [u8],
+    version: Version,
+    trailer: Trailer<'b>,
+    xref: XrefTable,
+    objects: BTreeMap<u32, OnceCell<Object<'b>>>,
+    object_streams: BTreeMap<u32, OnceCell<ObjectStream>>,
+    repairs: Vec<Repair>,
+    security: Option<SecurityHandler>,
+    limits: Limits,
+}
+
+impl<'b> Document<'b> {
+    pub fn load(input: &'b [u8]) -> Result<Document<'b>> {
+        Self::load_with_password(input, b"")
+    }
+    /// Loads an encrypted file, `password` being either its user or its owner password.
+    pub fn load_with_password(input: &'b [u8], password: &[u8]) -> Result<Document<'b>> {
+        Self::load_with_limits(input, password, Limits::default())
+    }
+    /// Loads a file from an untrusted source, failing once it needs more resources than `limits`
+    /// allow. The limits also apply to every object loaded later.
+    pub fn load_with_limits(
+        input: &'b [u8],
+        password: &[u8],
+        limits: Limits,
+    ) -> Result<Document<'b>> {
+        let (_, version) = Version::find(input).ok_or(Error::InvalidHeader)?;
+        let start = StartXref::find(input).ok_or(Error::MissingStartXref)?;
+        let (xref, trailer) = load_xref(input, start.get(), &limits)?;
+        let mut document = Self::new(input, version, xref, trailer, limits)?;
+        document.init_security(password)?;
+        Ok(document)
+    }
+    fn new(
+        input: &'b [u8],
+        version: Version,
+        xref: XrefTable,
+        trailer: Trailer<'b>,
+        limits: Limits,
+    ) -> Result<Self> {
+        if xref.len() > limits.max_objects {
+            return Err(Error::LimitExceeded("object count"));
+        }
+        let objects = xref.iter().map(|(n, _)| (*n, OnceCell::new())).collect();
+        let object_streams = xref
+            .iter()
+            .filter_map(|(_, entry)| match entry {
+                XrefEntry::Compressed { stream, .. } => Some((*stream, OnceCell::new())),
+                _ => None,
+            })
+            .collect();
+        Ok(Self {
+            input,
+            version,
+            trailer,
+            xref,
+            objects,
+            object_streams,
+            repairs: Vec::new(),
+            security: None,
+            limits,
+        })
+    }
+    fn init_security(&mut self, password: &[u8]) -> Result<()> {
+        let Some(encrypt) = self.trailer.encrypt() else {
+            return Ok(());
+        };
+        let encrypt: &Dictionary = self
+            .resolve(encrypt)?
+            .get_obj()
+            .ok_or(Error::UnsupportedEncryption("/Encrypt is not a dictionary"))?;
+        let id = self.trailer.id().unwrap_or_default();
+        self.security = Some(SecurityHandler::new(encrypt, id, password)?);
+        Ok(())
+    }
+    pub fn get_version(&self) -> Version {
+        self.version
+    }
+    pub fn get_trailer(&self) -> &Trailer<'b> {
+        &self.trailer
+    }
+    pub fn get_xref(&self) -> &XrefTable {
+        &self.xref
+    }
+    /// The problems that were worked around while loading the file, if it was repaired.
+    pub fn get_repairs(&self) -> &[Repair] {
+        &self.repairs
+    }
+    /// The security handler of an encrypted file.
+    pub fn get_security(&self) -> Option<&SecurityHandler> {
+        self.security.as_ref()
+    }
+    /// The limits the file was loaded with, to be passed on when decoding its streams.
+    pub fn get_limits(&self) -> &Limits {
+        &self.limits
+    }
+    pub fn get_object(&self, id: ObjectId) -> Result<&Object<'b>> {
+        let cell = self
+            .objects
+            .get(&id.number())
+            .ok_or(Error::ObjectNotFound(id))?;
+        if let Some(object) = cell.get() {
+            return self.check_generation(id, object);
+        }
+        let entry = self
+            .xref
+            .get(id.number())
+            .ok_or(Error::ObjectNotFound(id))?;
+        if entry.generation() != id.generation() {
+            return Err(Error::ObjectNotFound(id));
+        }
+        let object = self.load_object(id, *entry)?;
+        Ok(cell.get_or_init(|| object))
+    }
+    /// Follows `object` if it is a reference, otherwise returns it unchanged.
+    pub fn resolve<'a>(&'a self, object: &'a Object<'b>) -> Result<&'a Object<'b>> {
+        match object {
+            Object::Reference(r) => self.get_object(r.get()),
+            o => Ok(o),
+        }
+    }
+    pub fn get_catalog(&self) -> Result<&Dictionary<'b>> {
+        let root = self.trailer.root().ok_or(Error::MissingCatalog)?;
+        self.get_object(root)?
+            .get_obj()
+            .ok_or(Error::MissingCatalog)
+    }
+    fn check_generation<'a>(&self, id: ObjectId, object: &'a Object<'b>) -> Result<&'a Object<'b>> {
+        match self.xref.get(id.number()) {
+            Some(entry) if entry.generation() == id.generation() => Ok(object),
+            _ => Err(Error::ObjectNotFound(id)),
+        }
+    }
+    fn load_object(&self, id: ObjectId, entry: XrefEntry) -> Result<Object<'b>> {
+        match entry {
+            XrefEntry::Free { .. } => Err(Error::ObjectNotFound(id)),
+            XrefEntry::InUse { offset, .. } => {
+                let input = self
+                    .input
+                    .get(offset..)
+                    .ok_or(Error::InvalidOffset(offset))?;
+                let (_, object) = IndirectObject::parse_with_length(input, &self.limits, |id| {
+                    self.get_length(id)
+                })
+                .map_err(|e| Error::from(e).locate(self.input))?;
+                if object.get_id() != id {
+                    return Err(Error::ObjectNotFound(id));
+                }
+                let mut object = object.into_object();
+                // Objects inside object streams were decrypted along with the stream.
+                if let Some(security) = &self.security
+                    && !self.is_encrypt_dictionary(id)
+                {
+                    security.decrypt(id, &mut object)?;
+                }
+                Ok(object)
+            }
+            XrefEntry::Compressed { stream, index } => {
+                let object_stream = self.get_object_stream(stream)?;
+                let object = match object_stream.get(index.try_into().unwrap_or(usize::MAX)) {
+                    Some((found, object)) if found == id => object,
+                    _ => object_stream
+                        .find(id.number())
+                        .ok_or(Error::ObjectNotFound(id))?,
+                };
+                Ok(object.into_owned())
+            }
+        }
+    }
+    /// Looks up an indirect stream `/Length` without resolving the lengths of other streams on
+    /// the way, as those could lead back to the stream being loaded.
+    fn get_length(&self, id: ObjectId) -> Option<usize> {
+        let input = self.input.get(self.xref.offset(id)?..)?;
+        let (_, object) = IndirectObject::parse_limited(input, &self.limits).ok()?;
+        if object.get_id() != id {
+            return None;
+        }
+        let length: &Integer = object.get_object().get_obj()?;
+        length.get().try_into().ok()
+    }
+    fn is_encrypt_dictionary(&self, id: ObjectId) -> bool {
+        matches!(self.trailer.encrypt(), Some(Object::Reference(r)) if r.get() == id)
+    }
+    fn get_object_stream(&self, number: u32) -> Result<&ObjectStream> {
+        let cell = self
+            .object_streams
+            .get(&number)
+            .ok_or(Error::InvalidObjectStream)?;
+        if let Some(object_stream) = cell.get() {
+            return Ok(object_stream);
+        }
+        // Object streams may not be compressed themselves. Loading one that claims to be would
+        // recurse forever when it lies in itself or in a stream that refers back to it.
+        if !matches!(self.xref.get(number), Some(XrefEntry::InUse { .. })) {
+            return Err(Error::InvalidObjectStream);
+        }
+        let stream: &Stream = self
+            .get_object(ObjectId::new(number, 0))?
+            .get_obj()
+            .ok_or(Error::InvalidObjectStream)?;
+        let object_stream = ObjectStream::new_limited(stream, &self.limits)?;
+        Ok(cell.get_or_init(|| object_stream))
+    }
+}
+
+/// Reads the cross-reference section at `start` and every older section reachable through
+/// `/Prev` and `/XRefStm`, returning the merged table and the newest trailer.
+fn load_xref<'b>(
+    input: &'b [u8],
+    start: usize,
+    limits: &Limits,
+) -> Result<(XrefTable, Trailer<'b>)> {
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![start];
+    let mut xref = XrefTable::new();
+    let mut newest = None;
+    while let Some(offset) = pending.pop() {
+        if !visited.insert(offset) {
+            continue;
+        }
+        let section = input.get(offset..).ok_or(Error::InvalidOffset(offset))?;
+        let (table, trailer) = if let Ok((_, section)) = XrefSection::parse(section) {
+            section.into_parts()
+        } else {
+            let (_, object) = IndirectObject::parse_limited(section, limits)
+                .map_err(|_| Error::InvalidXref(offset))?;
+            let id = object.get_id();
+            let Object::Stream(stream) = object.into_object() else {
+                return Err(Error::InvalidXref(offset));
+            };
+            // Only running out of resources is reported as such, any other failure means the
+            // offset does not point to a cross-reference section.
+            let stream = XrefStream::new_limited(id, stream, limits).map_err(|err| match err {
+                Error::LimitExceeded(_) => err,
+                _ => Error::InvalidXref(offset),
+            })?;
+            stream.into_parts()
+        };
+        xref.merge_older(table);
+        // The older sections are pushed first so the hybrid `/XRefStm` is read before them.
+        if let Some(prev) = trailer.prev() {
+            pending.push(prev);
+        }
+        if let Some(xref_stream) = trailer.xref_stream() {
+            pending.push(xref_stream);
+        }
+        newest.get_or_insert(trailer);
+    }
+    let trailer = newest.ok_or(Error::MissingStartXref)?;
+    Ok((xref, trailer))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::objects::{Integer, Name};
+
+    use super::*;
+
+    /// Builds a file with a correct classic cross-reference table for `objects`.
+    pub fn build_pdf(objects: &[&str], trailer: &str) -> Vec<u8> {
+        let mut res = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        for (number, object) in (1..).zip(objects) {
+            offsets.push(res.len());
+            res.extend_from_slice(format!("{number} 0 obj\n{object}\nendobj\n").as_bytes());
+        }
+        let start = res.len();
+        res.extend_from_slice(
+            format!(
+                "xref\n0 {}\n0000000000 65535 f \n",
+                objects.len().saturating_add(1)
+            )
+            .as_bytes(),
+        );
+        for offset in offsets {
+            res.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        res.extend_from_slice(
+            format!("trailer\n{trailer}\nstartxref\n{start}\n%%EOF\n").as_bytes(),
+        );
+        res
+    }
+
+    #[test]
+    fn load_1() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [] /Count 0>>",
+            ],
+            "<</Size 3 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        assert_eq!(document.get_version(), Version::new(1, 4));
+        let catalog = document.get_catalog().unwrap();
+        let pages = document
+            .resolve(catalog.get(&Name::new(b"Pages")).unwrap())
+            .unwrap();
+        let pages: &Dictionary = pages.get_obj().unwrap();
+        let count: &Integer = pages.get(&Name::new(b"Count")).unwrap().get_obj().unwrap();
+        assert_eq!(count.get(), 0);
+    }
+    #[test]
+    fn load_2() {
+        let input = build_pdf(&["42"], "<</Size 2 /Root 1 0 R>>");
+        let document = Document::load(&input).unwrap();
+        let first = document.get_object(ObjectId::new(1, 0)).unwrap();
+        let second = document.get_object(ObjectId::new(1, 0)).unwrap();
+        assert!(core::ptr::eq(first, second));
+        assert_eq!(
+            document.get_object(ObjectId::new(1, 1)),
+            Err(Error::ObjectNotFound(ObjectId::new(1, 1)))
+        );
+        assert_eq!(
+            document.get_object(ObjectId::new(0, 65535)),
+            Err(Error::ObjectNotFound(ObjectId::new(0, 65535)))
+        );
+        assert_eq!(
+            document.get_object(ObjectId::new(7, 0)),
+            Err(Error::ObjectNotFound(ObjectId::new(7, 0)))
+        );
+        assert!(document.get_catalog().is_err());
+    }
+    #[test]
+    fn load_incremental_update() {
+        let mut input = build_pdf(&["(old)", "<</Type /Catalog>>"], "<</Size 3 /Root 2 0 R>>");
+        let prev = StartXref::find(&input).unwrap().get();
+        let offset = input.len();
+        input.extend_from_slice(b"1 0 obj\n(new)\nendobj\n");
+        let start = input.len();
+        input.extend_from_slice(
+            format!(
+                "xref\n1 1\n{offset:010} 00000 n \ntrailer\n<</Size 3 /Root 2 0 R /Prev {prev}>>\nstartxref\n{start}\n%%EOF\n"
+            )
+            .as_bytes(),
+        );
+        let document = Document::load(&input).unwrap();
+        let s: &crate::objects::String = document
+            .get_object(ObjectId::new(1, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(s.get(), b"new");
+        assert!(document.get_catalog().is_ok());
+        assert_eq!(document.get_trailer().prev(), Some(prev));
+    }
+    #[test]
+    fn load_prev_loop() {
+        let input = build_pdf(&["true"], "<</Size 2 /Prev 9999>>");
+        assert!(matches!(
+            Document::load(&input),
+            Err(Error::InvalidOffset(9999))
+        ));
+        let mut input = build_pdf(&["true"], "<</Size 2>>");
+        let start = StartXref::find(&input).unwrap().get();
+        let text = std::string::String::from_utf8(input.clone()).unwrap();
+        input = text
+            .replace("<</Size 2>>", &format!("<</Size 2 /Prev {start}>>"))
+            .into_bytes();
+        assert!(Document::load(&input).is_ok());
+    }
+    #[test]
+    fn load_object_stream() {
+        let objstm = "<</Type /ObjStm /N 2 /First 8 /Length 39>>stream\n3 0 4 19<</Type /Catalog>> (compressed)\nendstream";
+        let mut input = b"%PDF-1.5\n".to_vec();
+        let objstm_offset = input.len();
+        input.extend_from_slice(format!("1 0 obj\n{objstm}\nendobj\n").as_bytes());
+        let start = input.len();
+        let [objstm_hi, objstm_lo] = u16::try_from(objstm_offset).unwrap().to_be_bytes();
+        let [start_hi, start_lo] = u16::try_from(start).unwrap().to_be_bytes();
+        let data = [
+            0, 0, 0, 255, 1, objstm_hi, objstm_lo, 0, 1, start_hi, start_lo, 0, 2, 0, 1, 0, 2, 0,
+            1, 1,
+        ];
+        input.extend_from_slice(
+            format!(
+                "2 0 obj\n<</Type /XRef /Size 5 /Root 3 0 R /W [1 2 1] /Length {}>>stream\n",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        input.extend_from_slice(&data);
+        input.extend_from_slice(
+            format!("\nendstream\nendobj\nstartxref\n{start}\n%%EOF\n").as_bytes(),
+        );
+        let document = Document::load(&input).unwrap();
+        let catalog = document.get_catalog().unwrap();
+        let t: &Name = catalog.get(&Name::new(b"Type")).unwrap().get_obj().unwrap();
+        assert_eq!(t.get(), b"Catalog");
+        let s: &crate::objects::String = document
+            .get_object(ObjectId::new(4, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(s.get(), b"compressed");
+    }
+    #[test]
+    fn load_compressed_object_stream() {
+        // Objects 1 and 2 both claim to lie in object stream 2.
+        let mut input = b"%PDF-1.5\n".to_vec();
+        let start = input.len();
+        let [start_hi, start_lo] = u16::try_from(start).unwrap().to_be_bytes();
+        let data = [
+            0, 0, 0, 255, 2, 0, 2, 0, 2, 0, 2, 0, 1, start_hi, start_lo, 0,
+        ];
+        input.extend_from_slice(
+            format!(
+                "3 0 obj\n<</Type /XRef /Size 4 /Root 1 0 R /W [1 2 1] /Length {}>>stream\n",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        input.extend_from_slice(&data);
+        input.extend_from_slice(
+            format!("\nendstream\nendobj\nstartxref\n{start}\n%%EOF\n").as_bytes(),
+        );
+        let document = Document::load(&input).unwrap();
+        assert_eq!(
+            document.get_object(ObjectId::new(1, 0)).unwrap_err(),
+            Error::InvalidObjectStream
+        );
+        assert_eq!(
+            document.get_object(ObjectId::new(2, 0)).unwrap_err(),
+            Error::InvalidObjectStream
+        );
+    }
+    #[test]
+    fn load_invalid() {
+        assert_eq!(Document::load(b"hello").unwrap_err(), Error::InvalidHeader);
+        assert_eq!(
+            Document::load(b"%PDF-1.4\n%%EOF").unwrap_err(),
+            Error::MissingStartXref
+        );
+    }
+    /// A file encrypted with RC4 for the user password `user` and the owner password `owner`.
+    pub fn encrypted_pdf() -> Vec<u8> {
+        build_pdf(
+            &[
+                "<< /Type /Catalog >>",
+                "<7b6f18d61fe2d0724591b4>",
+                "<< /Filter /Standard /V 2 /R 3 /Length 128 /P -3904 \
+                 /O <0ba3835f88f90388e74e54584125ce142be0de24c6b0d37746e075b891756671> \
+                 /U <c9bbe2a3956ec521581a87749e0bc91a00000000000000000000000000000000> >>",
+            ],
+            "<< /Size 4 /Root 1 0 R /Encrypt 3 0 R \
+             /ID [<0123456789abcdeffedcba9876543210> <0123456789abcdeffedcba9876543210>] >>",
+        )
+    }
+
+    #[test]
+    fn load_encrypted() {
+        let input = encrypted_pdf();
+        assert_eq!(
+            Document::load(&input).unwrap_err(),
+            Error::IncorrectPassword
+        );
+        for password in [b"user".as_slice(), b"owner"] {
+            let document = Document::load_with_password(&input, password).unwrap();
+            assert!(document.get_security().is_some());
+            let s: &crate::objects::String = document
+                .get_object(ObjectId::new(2, 0))
+                .unwrap()
+                .get_obj()
+                .unwrap();
+            assert_eq!(s.get(), b"Secret text");
+            let encrypt: &Dictionary = document
+                .get_object(ObjectId::new(3, 0))
+                .unwrap()
+                .get_obj()
+                .unwrap();
+            let u: &crate::objects::String =
+                encrypt.get(&Name::new(b"U")).unwrap().get_obj().unwrap();
+            assert_eq!(u.get().len(), 32);
+        }
+    }
+    #[test]
+    fn load_parse_error() {
+        let input = build_pdf(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Kids [1 0 R /Count 1 >>",
+            ],
+            "<< /Size 3 /Root 1 0 R >>",
+        );
+        let document = Document::load(&input).unwrap();
+        let err = match document.get_object(ObjectId::new(2, 0)) {
+            Err(Error::Parse(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_object(), Some(ObjectId::new(2, 0)));
+        assert_eq!(err.get_path(), [Name::new(b"Kids")]);
+        let offset = err.get_offset().unwrap();
+        assert_eq!(&input[offset..offset.saturating_add(2)], b">>");
+        assert!(err.to_string().contains("in object 2 0 at /Kids"));
+    }
+    #[test]
+    fn load_with_limits() {
+        let input = build_pdf(
+            &["<</Type /Catalog>>", "[[[1]]]"],
+            "<</Size 3 /Root 1 0 R>>",
+        );
+        let limits = Limits {
+            max_objects: 2,
+            ..Limits::default()
+        };
+        assert_eq!(
+            Document::load_with_limits(&input, b"", limits).unwrap_err(),
+            Error::LimitExceeded("object count")
+        );
+        assert_eq!(
+            Document::load_or_repair_with_limits(&input, b"", limits).unwrap_err(),
+            Error::LimitExceeded("object count")
+        );
+        let limits = Limits {
+            max_depth: 2,
+            ..Limits::default()
+        };
+        let document = Document::load_with_limits(&input, b"", limits).unwrap();
+        assert!(document.get_catalog().is_ok());
+        assert!(document.get_object(ObjectId::new(2, 0)).is_err());
+    }
+    #[test]
+    fn load_xref_stream_with_limits() {
+        let mut input = b"%PDF-1.5\n".to_vec();
+        let catalog = u16::try_from(input.len()).unwrap().to_be_bytes();
+        input.extend_from_slice(b"1 0 obj\n<</Type /Catalog>>\nendobj\n");
+        let start = input.len();
+        let [start_hi, start_lo] = u16::try_from(start).unwrap().to_be_bytes();
+        let entries = [
+            0, 0, 0, 255, 1, catalog[0], catalog[1], 0, 1, start_hi, start_lo, 0,
+        ];
+        let data = miniz_oxide::deflate::compress_to_vec_zlib(&entries, 6);
+        input.extend_from_slice(
+            format!(
+                "2 0 obj\n<</Type /XRef /Size 3 /Root 1 0 R /W [1 2 1] /Filter /Fl /Length {}>>\
+                 stream\n",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        input.extend_from_slice(&data);
+        input.extend_from_slice(
+            format!("\nendstream\nendobj\nstartxref\n{start}\n%%EOF\n").as_bytes(),
+        );
+        let document = Document::load(&input).unwrap();
+        assert!(document.get_catalog().is_ok());
+        let limits = Limits {
+            max_decoded_size: 8,
+            ..Limits::default()
+        };
+        assert_eq!(
+            Document::load_with_limits(&input, b"", limits).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+    #[test]
+    fn load_indirect_length() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog>>",
+                "<</Length 3 0 R>>stream\na endstream b\nendstream",
+                "13",
+            ],
+            "<</Size 4 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let stream: &Stream = document
+            .get_object(ObjectId::new(2, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(stream.get_data(), b"a endstream b");
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeSet;
+
+use super::Document;
+use crate::{
+    content::Content,
+    error::{Error, Result},
+    geometry::Rectangle,
+    objects::{Dictionary, Name, Object, ObjectId, Stream},
+};
+
+/// The size of a US Letter page, used when no `/MediaBox` is given.
+const DEFAULT_MEDIA_BOX: Rectangle = Rectangle::new(0.0, 0.0, 612.0, 792.0);
+
+/// A leaf of the page tree with the attributes it inherits from its ancestors resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<'a, 'b> {
+    id: ObjectId,
+    dictionary: &'a Dictionary<'b>,
+    resources: Option<&'a Dictionary<'b>>,
+    media_box: Rectangle,
+    crop_box: Option<Rectangle>,
+    rotation: i64,
+}
+
+/// The inheritable attributes of a page tree node.
+#[derive(Debug, Clone, Copy, Default)]
+struct Inherited<'a, 'b> {
+    resources: Option<&'a Dictionary<'b>>,
+    media_box: Option<Rectangle>,
+    crop_box: Option<Rectangle>,
+    rotate: Option<i64>,
+}
+
+impl<'a, 'b> Page<'a, 'b> {
+    pub fn get_id(&self) -> ObjectId {
+        self.id
+    }
+    /// The page dictionary itself, without inherited attributes.
+    pub fn get_dictionary(&self) -> &'a Dictionary<'b> {
+        self.dictionary
+    }
+    pub fn get_resources(&self) -> Option<&'a Dictionary<'b>> {
+        self.resources
+    }
+    pub fn get_media_box(&self) -> Rectangle {
+        self.media_box
+    }
+    /// The visible region of the page, which defaults to and is clipped by the media box.
+    pub fn get_crop_box(&self) -> Rectangle {
+        self.crop_box
+            .and_then(|crop_box| crop_box.intersect(&self.media_box))
+            .unwrap_or(self.media_box)
+    }
+    pub fn get_bleed_box(&self) -> Rectangle {
+        self.get_box(b"BleedBox")
+    }
+    pub fn get_trim_box(&self) -> Rectangle {
+        self.get_box(b"TrimBox")
+    }
+    pub fn get_art_box(&self) -> Rectangle {
+        self.get_box(b"ArtBox")
+    }
+    /// The clockwise rotation of the page when displayed, one of 0, 90, 180 and 270.
+    pub fn get_rotation(&self) -> i64 {
+        self.rotation
+    }
+    /// The boxes that are not inherited default to the crop box and are clipped by it.
+    fn get_box(&self, key: &[u8]) -> Rectangle {
+        let crop_box = self.get_crop_box();
+        self.dictionary
+            .get(&Name::new(key))
+            .and_then(Object::as_array)
+            .and_then(|array| Rectangle::try_from(array).ok())
+            .and_then(|rect| rect.intersect(&crop_box))
+            .unwrap_or(crop_box)
+    }
+}
+
+impl<'b> Document<'b> {
+    /// Walks the page tree from the catalog and returns the pages in order. A page or subtree
+    /// listed more than once is only returned the first time.
+    pub fn pages(&self) -> Result<Vec<Page<'_, 'b>>> {
+        let root = self
+            .get_catalog()?
+            .get(&Name::new(b"Pages"))
+            .and_then(Object::as_reference)
+            .ok_or(Error::InvalidPageTree("missing /Pages in the catalog"))?;
+        let mut pages = Vec::new();
+        let mut visited = BTreeSet::new();
+        // The nodes from the root to the current one, only these can close a cycle.
+        let mut ancestors = Vec::new();
+        let mut pending = vec![(root, Inherited::default(), 0)];
+        while let Some((id, inherited, depth)) = pending.pop() {
+            ancestors.truncate(depth);
+            if ancestors.contains(&id) {
+                return Err(Error::InvalidPageTree("cycle in the page tree"));
+            }
+            if !visited.insert(id) {
+                continue;
+            }
+            ancestors.push(id);
+            let node = self
+                .get_object(id)?
+                .as_dict()
+                .ok_or(Error::InvalidPageTree("page tree node is not a dictionary"))?;
+            let inherited = self.inherit(node, inherited);
+            let kids = node.get(&Name::new(b"Kids"));
+            let is_page = match node.get(&Name::new(b"Type")).and_then(Object::as_name) {
+                Some(t) => t == b"Page",
+                None => kids.is_none(),
+            };
+            if is_page {
+                pages.push(Page {
+                    id,
+                    dictionary: node,
+                    resources: inherited.resources,
+                    media_box: inherited.media_box.unwrap_or(DEFAULT_MEDIA_BOX),
+                    crop_box: inherited.crop_box,
+                    rotation: normalize_rotation(inherited.rotate.unwrap_or(0)),
+                });
+                continue;
+            }
+            let Some(kids) = kids.map(|kids| self.resolve(kids)).transpose()? else {
+                continue;
+            };
+            let kids = kids
+                .as_array()
+                .ok_or(Error::InvalidPageTree("/Kids is not an array"))?;
+            // Pushed in reverse so that the first kid is visited next.
+            for kid in kids.get().iter().rev() {
+                if let Some(kid) = kid.as_reference() {
+                    pending.push((kid, inherited, ancestors.len()));
+                }
+            }
+        }
+        Ok(pages)
+    }
+    /// Decodes and parses the `/Contents` of a page, concatenating the streams when it is an
+    /// array. A page without contents is empty.
+    pub fn get_page_content(&self, page: &Page<'_, 'b>) -> Result<Content<'static>> {
+        let Some(contents) = page.get_dictionary().get(&Name::new(b"Contents")) else {
+            return Ok(Content::default());
+        };
+        let contents = self.resolve(contents)?;
+        let parts = match contents.as_array() {
+            Some(array) => array.get(),
+            None => std::slice::from_ref(contents),
+        };
+        let streams = parts
+            .iter()
+            .map(|part| {
+                let part = self.resolve(part)?;
+                part.as_stream().ok_or(Error::UnexpectedType {
+                    expected: "stream",
+                    found: part.type_name(),
+                })
+            })
+            .collect::<Result<Vec<&Stream>>>()?;
+        Content::decode(&streams, &self.limits)
+    }
+    /// Overrides the inherited attributes with those set on `node`, ignoring invalid values.
+    fn inherit<'a>(
+        &'a self,
+        node: &'a Dictionary<'b>,
+        inherited: Inherited<'a, 'b>,
+    ) -> Inherited<'a, 'b> {
+        let get = |key: &[u8]| {
+            node.get(&Name::new(key))
+                .and_then(|value| self.resolve(value).ok())
+        };
+        let get_box = |key: &[u8]| {
+            get(key)
+                .and_then(Object::as_array)
+                .and_then(|array| Rectangle::try_from(array).ok())
+        };
+        Inherited {
+            resources: get(b"Resources")
+                .and_then(Object::as_dict)
+                .or(inherited.resources),
+            media_box: get_box(b"MediaBox").or(inherited.media_box),
+            crop_box: get_box(b"CropBox").or(inherited.crop_box),
+            rotate: get(b"Rotate").and_then(Object::as_i64).or(inherited.rotate),
+        }
+    }
+}
+
+fn normalize_rotation(rotate: i64) -> i64 {
+    match rotate.rem_euclid(360) {
+        rotation @ (0 | 90 | 180 | 270) => rotation,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::document::tests::build_pdf;
+
+    use super::*;
+    #[test]
+    fn pages_1() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [3 0 R 4 0 R] /Count 3 /MediaBox [0 0 595 842] \
+                 /Resources <</Font <<>>>> /Rotate 90>>",
+                "<</Type /Page /Parent 2 0 R /Rotate -90 /CropBox [10 10 700 500]>>",
+                "<</Type /Pages /Parent 2 0 R /Kids [5 0 R] /Count 1 /Resources 6 0 R>>",
+                "<</Type /Page /Parent 4 0 R /MediaBox [0 0 200 100] /TrimBox [5 5 50 50]>>",
+                "<</ProcSet [/PDF]>>",
+            ],
+            "<</Size 7 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let pages = document.pages().unwrap();
+        assert_eq!(pages.len(), 2);
+
+        let first = &pages[0];
+        assert_eq!(first.get_id(), ObjectId::new(3, 0));
+        assert_eq!(
+            first.get_media_box(),
+            Rectangle::new(0.0, 0.0, 595.0, 842.0)
+        );
+        assert_eq!(
+            first.get_crop_box(),
+            Rectangle::new(10.0, 10.0, 595.0, 500.0)
+        );
+        assert_eq!(first.get_trim_box(), first.get_crop_box());
+        assert_eq!(first.get_rotation(), 270);
+        assert!(
+            first
+                .get_resources()
+                .unwrap()
+                .contains_key(&Name::new(b"Font"))
+        );
+
+        let second = &pages[1];
+        assert_eq!(second.get_id(), ObjectId::new(5, 0));
+        assert_eq!(
+            second.get_media_box(),
+            Rectangle::new(0.0, 0.0, 200.0, 100.0)
+        );
+        assert_eq!(second.get_crop_box(), second.get_media_box());
+        assert_eq!(second.get_trim_box(), Rectangle::new(5.0, 5.0, 50.0, 50.0));
+        assert_eq!(second.get_rotation(), 90);
+        assert!(
+            second
+                .get_resources()
+                .unwrap()
+                .contains_key(&Name::new(b"ProcSet"))
+        );
+    }
+    #[test]
+    fn pages_defaults() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Kids [3 0 R]>>",
+                "<</Rotate 45>>",
+            ],
+            "<</Size 4 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let pages = document.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].get_media_box(), DEFAULT_MEDIA_BOX);
+        assert_eq!(pages[0].get_rotation(), 0);
+        assert!(pages[0].get_resources().is_none());
+    }
+    #[test]
+    fn pages_cycle() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [3 0 R]>>",
+                "<</Type /Pages /Kids [2 0 R]>>",
+            ],
+            "<</Size 4 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        assert_eq!(
+            document.pages().unwrap_err(),
+            Error::InvalidPageTree("cycle in the page tree")
+        );
+    }
+    #[test]
+    fn pages_shared_kids() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [3 0 R 3 0 R 4 0 R 5 0 R]>>",
+                "<</Type /Page>>",
+                "<</Type /Pages /Kids [6 0 R]>>",
+                "<</Type /Pages /Kids [6 0 R]>>",
+                "<</Type /Pages /Kids [7 0 R]>>",
+                "<</Type /Page>>",
+            ],
+            "<</Size 8 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let ids: Vec<ObjectId> = document.pages().unwrap().iter().map(Page::get_id).collect();
+        assert_eq!(ids, [ObjectId::new(3, 0), ObjectId::new(7, 0)]);
+    }
+    #[test]
+    fn page_content() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Kids [3 0 R 6 0 R]>>",
+                "<</Type /Page /Contents [4 0 R 5 0 R]>>",
+                "<</Length 8>>stream\nq 2 0 0\nendstream",
+                "<</Length 13>>stream\n2 0 0 cm Q\nendstream",
+                "<</Type /Page>>",
+            ],
+            "<</Size 7 /Root 1 0 R>>",
+        );
+        let document = Document::load(&input).unwrap();
+        let pages = document.pages().unwrap();
+        let content = document.get_page_content(&pages[0]).unwrap();
+        let operators: Vec<&str> = content.get().iter().map(|op| op.get_operator()).collect();
+        assert_eq!(operators, ["q", "cm", "Q"]);
+        assert_eq!(content.get()[1].get_operands().len(), 6);
+        let content = document.get_page_content(&pages[1]).unwrap();
+        assert!(content.get().is_empty());
+    }
+    #[test]
+    fn pages_missing_root() {
+        let input = build_pdf(&["<</Type /Catalog>>"], "<</Size 2 /Root 1 0 R>>");
+        let document = Document::load(&input).unwrap();
+        assert!(matches!(document.pages(), Err(Error::InvalidPageTree(_))));
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    error::{Error, Result},
+    lexer::is_regular,
+    limits::Limits,
+    objects::{Dictionary, GetObj, IndirectObject, Name, Object, ObjectId, Reference, Stream},
+    xref::{ObjectStream, Trailer, XrefEntry, XrefTable},
+};
+
+use super::{Document, Version};
+
+/// A problem that was worked around while loading a damaged file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// The file has no `%PDF-` header, version 1.7 was assumed.
+    MissingHeader,
+    /// The cross-reference data was unusable and was rebuilt from the object headers in the body.
+    RebuiltXref { cause: Error, objects: usize },
+    /// An `N G obj` header was found at this offset but the object could not be parsed.
+    SkippedObject(usize),
+    /// The trailer dictionary was taken from the `trailer` or `/Type /XRef` found at this offset.
+    RecoveredTrailer(usize),
+    /// No usable trailer exists, the catalog was identified by its `/Type /Catalog` entry.
+    InferredCatalog(ObjectId),
+}
+
+impl<'b> Document<'b> {
+    /// Loads the file like [`Document::load`], but rebuilds the cross-reference table by scanning
+    /// the body for object definitions when the file is damaged.
+    pub fn load_or_repair(input: &'b [u8]) -> Result<Document<'b>> {
+        Self::load_or_repair_with_limits(input, b"", Limits::default())
+    }
+    /// Like [`Document::load_or_repair`], for a file protected by a user or owner password.
+    pub fn load_or_repair_with_password(input: &'b [u8], password: &[u8]) -> Result<Document<'b>> {
+        Self::load_or_repair_with_limits(input, password, Limits::default())
+    }
+    /// Like [`Document::load_or_repair`], within the resources allowed by `limits`.
+    pub fn load_or_repair_with_limits(
+        input: &'b [u8],
+        password: &[u8],
+        limits: Limits,
+    ) -> Result<Document<'b>> {
+        let cause = match Self::load_with_limits(input, password, limits) {
+            Ok(document) => match document.get_catalog() {
+                Ok(_) => return Ok(document),
+                Err(err) => err,
+            },
+            Err(
+                err @ (Error::IncorrectPassword
+                | Error::UnsupportedEncryption(_)
+                | Error::LimitExceeded(_)),
+            ) => {
+                return Err(err);
+            }
+            Err(err) => err,
+        };
+        Self::repair_with_limits(input, cause, password, limits)
+    }
+    /// Ignores the cross-reference data of the file and rebuilds it by scanning the body.
+    pub fn repair(input: &'b [u8], cause: Error) -> Result<Document<'b>> {
+        Self::repair_with_limits(input, cause, b"", Limits::default())
+    }
+    /// Like [`Document::repair`], decrypting with `password` and within the resources allowed
+    /// by `limits`.
+    pub fn repair_with_limits(
+        input: &'b [u8],
+        cause: Error,
+        password: &[u8],
+        limits: Limits,
+    ) -> Result<Document<'b>> {
+        let mut repairs = Vec::new();
+        let version = match Version::find(input) {
+            Some((_, version)) => version,
+            None => {
+                repairs.push(Repair::MissingHeader);
+                Version::new(1, 7)
+            }
+        };
+        let scan = scan_objects(input, &limits);
+        repairs.push(Repair::RebuiltXref {
+            cause,
+            objects: scan.xref.len(),
+        });
+        repairs.extend(scan.skipped.iter().copied().map(Repair::SkippedObject));
+        let trailer = recover_trailer(input, &scan, &mut repairs)?;
+        let mut document = Self::new(input, version, scan.xref, trailer, limits)?;
+        document.repairs = repairs;
+        document.init_security(password)?;
+        Ok(document)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Scan<'b> {
+    xref: XrefTable,
+    skipped: Vec<usize>,
+    catalog: Option<ObjectId>,
+    xref_streams: Vec<(usize, Dictionary<'b>)>,
+}
+
+fn scan_objects<'b>(input: &'b [u8], limits: &Limits) -> Scan<'b> {
+    let mut scan = Scan::default();
+    let mut object_streams = Vec::new();
+    let mut offset = 0;
+    while let Some(rest) = input.get(offset..).filter(|rest| !rest.is_empty()) {
+        if starts_token(input, offset) && IndirectObject::parse_header(rest).is_ok() {
+            if let Ok((rem, object)) = IndirectObject::parse_limited(rest, limits) {
+                let id = object.get_id();
+                scan.xref.insert(
+                    id.number(),
+                    XrefEntry::InUse {
+                        offset,
+                        generation: id.generation(),
+                    },
+                );
+                match object.into_object() {
+                    Object::Dictionary(dict) if has_type(&dict, b"Catalog") => {
+                        scan.catalog = Some(id);
+                    }
+                    Object::Stream(stream) if has_type(stream.get_info(), b"XRef") => {
+                        scan.xref_streams.push((offset, stream.info));
+                    }
+                    Object::Stream(stream) if has_type(stream.get_info(), b"ObjStm") => {
+                        object_streams.push((id.number(), stream));
+                    }
+                    _ => {}
+                }
+                offset = input.len().saturating_sub(rem.len());
+                continue;
+            }
+            scan.skipped.push(offset);
+        }
+        offset = offset.saturating_add(1);
+    }
+    for (number, stream) in object_streams {
+        add_object_stream(&mut scan, number, &stream, limits);
+    }
+    scan
+}
+
+/// Registers the objects of an object stream, unless they are also defined directly in the body.
+fn add_object_stream(scan: &mut Scan, number: u32, stream: &Stream, limits: &Limits) {
+    let Ok(object_stream) = ObjectStream::new_limited(stream, limits) else {
+        return;
+    };
+    for index in 0..object_stream.len() {
+        let Some((id, object)) = object_stream.get(index) else {
+            continue;
+        };
+        if scan.xref.get(id.number()).is_some() {
+            continue;
+        }
+        if let Object::Dictionary(dict) = &object
+            && has_type(dict, b"Catalog")
+            && scan.catalog.is_none()
+        {
+            scan.catalog = Some(id);
+        }
+        if let Ok(index) = index.try_into() {
+            scan.xref.insert(
+                id.number(),
+                XrefEntry::Compressed {
+                    stream: number,
+                    index,
+                },
+            );
+        }
+    }
+}
+
+fn recover_trailer<'b>(
+    input: &'b [u8],
+    scan: &Scan<'b>,
+    repairs: &mut Vec<Repair>,
+) -> Result<Trailer<'b>> {
+    let keyword = b"trailer";
+    let mut candidates: Vec<(usize, Trailer<'b>)> = input
+        .windows(keyword.len())
+        .enumerate()
+        .filter(|(_, window)| *window == keyword)
+        .filter_map(|(offset, _)| {
+            let (_, trailer) = Trailer::parse(input.get(offset..)?).ok()?;
+            Some((offset, trailer))
+        })
+        .collect();
+    candidates.extend(
+        scan.xref_streams
+            .iter()
+            .map(|(offset, dict)| (*offset, Trailer::new(dict.clone()))),
+    );
+    candidates.sort_by_key(|(offset, _)| *offset);
+    let usable = candidates.iter().rev().find(|(_, trailer)| {
+        trailer
+            .root()
+            .is_some_and(|root| scan.xref.get(root.number()).is_some())
+    });
+    if let Some((offset, trailer)) = usable {
+        repairs.push(Repair::RecoveredTrailer(*offset));
+        return Ok(trailer.clone());
+    }
+    let catalog = scan.catalog.ok_or(Error::MissingCatalog)?;
+    let mut dict = candidates
+        .pop()
+        .map(|(_, trailer)| trailer.get().clone())
+        .unwrap_or_default();
+    dict.insert(
+        Name::new(b"Root"),
+        Object::Reference(Reference::new(catalog)),
+    );
+    repairs.push(Repair::InferredCatalog(catalog));
+    Ok(Trailer::new(dict))
+}
+
+fn has_type(dict: &Dictionary, expected: &[u8]) -> bool {
+    let t: Option<&Name> = dict.get(&Name::new(b"Type")).and_then(|t| t.get_obj());
+    t.is_some_and(|t| t.get() == expected)
+}
+
+fn starts_token(input: &[u8], offset: usize) -> bool {
+    let Some(prev) = offset.checked_sub(1) else {
+        return true;
+    };
+    input.get(prev).is_some_and(|c| !is_regular(*c))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        document::tests::build_pdf,
+        objects::{Integer, Stream, String},
+    };
+
+    use super::*;
+    #[test]
+    fn repair_wrong_startxref() {
+        let input = build_pdf(
+            &[
+                "<</Type /Catalog /Pages 2 0 R>>",
+                "<</Type /Pages /Count 0>>",
+            ],
+            "<</Size 3 /Root 1 0 R>>",
+        );
+        let text = std::string::String::from_utf8(input).unwrap();
+        let (body, _) = text.rsplit_once("startxref").unwrap();
+        let input = format!("{body}startxref\n3\n%%EOF\n").into_bytes();
+        assert!(Document::load(&input).is_err());
+        let document = Document::load_or_repair(&input).unwrap();
+        assert!(document.get_catalog().is_ok());
+        assert_eq!(
+            document.get_repairs()[0],
+            Repair::RebuiltXref {
+                cause: Error::InvalidXref(3),
+                objects: 2
+            }
+        );
+        assert!(matches!(
+            document.get_repairs()[1],
+            Repair::RecoveredTrailer(_)
+        ));
+    }
+    #[test]
+    fn repair_encrypted() {
+        let input = crate::document::tests::encrypted_pdf();
+        let text = std::string::String::from_utf8(input).unwrap();
+        let (body, _) = text.rsplit_once("startxref").unwrap();
+        let input = format!("{body}startxref\n3\n%%EOF\n").into_bytes();
+        assert_eq!(
+            Document::load_or_repair(&input).unwrap_err(),
+            Error::IncorrectPassword
+        );
+        let document = Document::load_or_repair_with_password(&input, b"user").unwrap();
+        assert_eq!(
+            document.get_repairs()[0],
+            Repair::RebuiltXref {
+                cause: Error::InvalidXref(3),
+                objects: 3
+            }
+        );
+        let s: &String = document
+            .get_object(ObjectId::new(2, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(s.get(), b"Secret text");
+    }
+    #[test]
+    fn repair_missing_xref() {
+        let input = b"%PDF-1.3\n1 0 obj\n<</Type /Catalog>>\nendobj\n2 0 obj\n(first)\nendobj\n2 0 obj\n(second)\nendobj\n3 0 obj\n<</Length 99>>stream\nabc\nendstream\nendobj\n";
+        let document = Document::load_or_repair(input).unwrap();
+        assert_eq!(document.get_version(), Version::new(1, 3));
+        assert_eq!(
+            document.get_repairs(),
+            [
+                Repair::RebuiltXref {
+                    cause: Error::MissingStartXref,
+                    objects: 3
+                },
+                Repair::InferredCatalog(ObjectId::new(1, 0))
+            ]
+        );
+        let s: &String = document
+            .get_object(ObjectId::new(2, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(s.get(), b"second");
+        // The wrong `/Length` is recovered by searching for `endstream`.
+        let stream: &Stream = document
+            .get_object(ObjectId::new(3, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(stream.get_data(), b"abc");
+    }
+    #[test]
+    fn repair_truncated_stream() {
+        let input = b"%PDF-1.3\n1 0 obj\n<</Type /Catalog>>\nendobj\n2 0 obj\n<</Length 99>>stream\nabc\nendobj\n";
+        let document = Document::load_or_repair(input).unwrap();
+        assert_eq!(
+            document.get_repairs(),
+            [
+                Repair::RebuiltXref {
+                    cause: Error::MissingStartXref,
+                    objects: 1
+                },
+                Repair::SkippedObject(43),
+                Repair::InferredCatalog(ObjectId::new(1, 0))
+            ]
+        );
+    }
+    #[test]
+    fn repair_object_stream() {
+        let input = b"1 0 obj\n<</Type /ObjStm /N 2 /First 8 /Length 28>>stream\n2 0 3 19<</Type /Catalog>> 7\nendstream\nendobj\n";
+        let document = Document::load_or_repair(input).unwrap();
+        assert_eq!(document.get_repairs()[0], Repair::MissingHeader);
+        assert!(document.get_catalog().is_ok());
+        let i: &Integer = document
+            .get_object(ObjectId::new(3, 0))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(i.get(), 7);
+    }
+    #[test]
+    fn repair_without_catalog() {
+        let input = b"%PDF-1.4\n1 0 obj\n(nothing)\nendobj\n";
+        assert_eq!(
+            Document::load_or_repair(input).unwrap_err(),
+            Error::MissingCatalog
+        );
+    }
+    #[test]
+    fn load_intact_file() {
+        let input = build_pdf(&["<</Type /Catalog>>"], "<</Size 2 /Root 1 0 R>>");
+        let document = Document::load_or_repair(&input).unwrap();
+        assert!(document.get_repairs().is_empty());
+    }
+}
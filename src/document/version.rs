@@ -0,0 +1,71 @@
+use nom::{
+    Parser,
+    bytes::complete::tag,
+    character::complete::{char, digit1},
+    sequence::{preceded, separated_pair},
+};
+
+use crate::error::ParseResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u8,
+    minor: u8,
+}
+
+impl Version {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Version> {
+        preceded(
+            tag("%PDF-"),
+            separated_pair(parse_digits, char('.'), parse_digits),
+        )
+        .map(|(major, minor)| Version { major, minor })
+        .parse(input)
+    }
+    /// Locates the `%PDF-x.y` header, which readers accept anywhere in the first 1024 bytes.
+    pub fn find(input: &[u8]) -> Option<(usize, Version)> {
+        let head = input.get(..1024).unwrap_or(input);
+        let position = head.windows(5).position(|window| window == b"%PDF-")?;
+        let (_, version) = Self::parse(head.get(position..)?).ok()?;
+        Some((position, version))
+    }
+    pub fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+    pub fn major(&self) -> u8 {
+        self.major
+    }
+    pub fn minor(&self) -> u8 {
+        self.minor
+    }
+}
+
+fn parse_digits(input: &[u8]) -> ParseResult<'_, u8> {
+    digit1
+        .map_res(core::str::from_utf8)
+        .map_res(str::parse)
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn parse_version_1() {
+        let (rem, parsed) = Version::parse(b"%PDF-1.7\n").unwrap();
+        assert_eq!(rem, b"\n");
+        assert_eq!(parsed, Version::new(1, 7));
+    }
+    #[test]
+    fn parse_version_2() {
+        assert!(Version::parse(b"%PDF-1").is_err());
+    }
+    #[test]
+    fn find_version_1() {
+        assert_eq!(
+            Version::find(b"garbage\n%PDF-2.0\n"),
+            Some((8, Version::new(2, 0)))
+        );
+        assert_eq!(Version::find(b"%!PS-Adobe-3.0"), None);
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use nom::{
+    IResult,
+    error::{ContextError, ErrorKind, FromExternalError},
+};
+
+use crate::objects::{Name, ObjectId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    UnsupportedFilter(std::string::String),
+    Decode(&'static str),
+    InvalidXrefStream,
+    InvalidObjectStream,
+    InvalidHeader,
+    MissingStartXref,
+    InvalidXref(usize),
+    InvalidOffset(usize),
+    ObjectNotFound(ObjectId),
+    MissingCatalog,
+    InvalidPageTree(&'static str),
+    IncorrectPassword,
+    UnsupportedEncryption(&'static str),
+    Decrypt(&'static str),
+    UnexpectedType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// An array had the wrong number of elements for what it stands for.
+    UnexpectedLength {
+        expected: &'static str,
+        length: usize,
+        found: usize,
+    },
+    LimitExceeded(&'static str),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The result of every public `parse` function.
+pub type ParseResult<'b, T> = IResult<&'b [u8], T, ParseError>;
+
+/// Why and where a parser failed.
+///
+/// Parsers only know how much input was left when they failed; [`ParseError::locate`] turns
+/// that into an offset once the whole buffer is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    remaining: usize,
+    offset: Option<usize>,
+    kind: ErrorKind,
+    reason: Option<&'static str>,
+    object: Option<ObjectId>,
+    path: Vec<Name<'static>>,
+}
+
+impl ParseError {
+    pub fn new(input: &[u8], kind: ErrorKind) -> Self {
+        Self {
+            remaining: input.len(),
+            offset: None,
+            kind,
+            reason: None,
+            object: None,
+            path: Vec::new(),
+        }
+    }
+    /// Builds the error of a parser that failed at `input` for `reason`.
+    pub fn with_reason(input: &[u8], reason: &'static str) -> Self {
+        Self::new(input, ErrorKind::Verify).reason(reason)
+    }
+    /// Computes the offset of the error in `input`, the buffer whose suffix was being parsed.
+    pub fn locate(mut self, input: &[u8]) -> Self {
+        if self.offset.is_none() {
+            self.offset = input.len().checked_sub(self.remaining);
+        }
+        self
+    }
+    /// Sets the reason unless a more specific one was already given by an inner parser.
+    pub fn reason(mut self, reason: &'static str) -> Self {
+        self.reason.get_or_insert(reason);
+        self
+    }
+    /// Records the indirect object that was being parsed, unless an inner one was already set.
+    pub fn object(mut self, id: ObjectId) -> Self {
+        self.object.get_or_insert(id);
+        self
+    }
+    /// Records that the error happened in the value of the dictionary entry `key`.
+    pub fn key(mut self, key: &Name) -> Self {
+        self.path.insert(0, key.clone().into_owned());
+        self
+    }
+    pub fn get_offset(&self) -> Option<usize> {
+        self.offset
+    }
+    pub fn get_kind(&self) -> ErrorKind {
+        self.kind
+    }
+    pub fn get_reason(&self) -> &str {
+        self.reason.unwrap_or(self.kind.description())
+    }
+    pub fn get_object(&self) -> Option<ObjectId> {
+        self.object
+    }
+    /// The keys of the nested dictionaries leading to the value that failed to parse.
+    pub fn get_path(&self) -> &[Name<'static>] {
+        &self.path
+    }
+}
+
+impl nom::error::ParseError<&[u8]> for ParseError {
+    fn from_error_kind(input: &[u8], kind: ErrorKind) -> Self {
+        Self::new(input, kind)
+    }
+    fn append(_input: &[u8], _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+    /// Keeps the error of the alternative that got furthest into the input.
+    fn or(self, other: Self) -> Self {
+        match self.remaining.cmp(&other.remaining) {
+            core::cmp::Ordering::Less => self,
+            core::cmp::Ordering::Equal if other.reason.is_none() => self,
+            _ => other,
+        }
+    }
+}
+
+impl ContextError<&[u8]> for ParseError {
+    fn add_context(_input: &[u8], ctx: &'static str, other: Self) -> Self {
+        other.reason(ctx)
+    }
+}
+
+impl<E> FromExternalError<&[u8], E> for ParseError {
+    fn from_external_error(input: &[u8], kind: ErrorKind, _e: E) -> Self {
+        Self::new(input, kind)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_reason())?;
+        if let Some(id) = self.object {
+            write!(f, " in object {} {}", id.number(), id.generation())?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " at ")?;
+            for key in &self.path {
+                write!(f, "/{}", std::string::String::from_utf8_lossy(key.get()))?;
+            }
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " (offset {offset})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// Fills in the offset of a parse error, `input` being the buffer that was parsed.
+    pub fn locate(self, input: &[u8]) -> Self {
+        match self {
+            Error::Parse(err) => Error::Parse(err.locate(input)),
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "parse error: {err}"),
+            Error::UnsupportedFilter(name) => write!(f, "unsupported filter /{name}"),
+            Error::Decode(reason) => write!(f, "failed to decode stream: {reason}"),
+            Error::InvalidXrefStream => write!(f, "invalid cross-reference stream"),
+            Error::InvalidObjectStream => write!(f, "invalid object stream"),
+            Error::InvalidHeader => write!(f, "missing %PDF- header"),
+            Error::MissingStartXref => write!(f, "missing startxref"),
+            Error::InvalidXref(offset) => {
+                write!(f, "no cross-reference section at offset {offset}")
+            }
+            Error::InvalidOffset(offset) => write!(f, "offset {offset} is out of bounds"),
+            Error::ObjectNotFound(id) => {
+                write!(f, "object {} {} not found", id.number(), id.generation())
+            }
+            Error::MissingCatalog => write!(f, "missing document catalog"),
+            Error::InvalidPageTree(reason) => write!(f, "invalid page tree: {reason}"),
+            Error::IncorrectPassword => write!(f, "incorrect password"),
+            Error::UnsupportedEncryption(reason) => write!(f, "unsupported encryption: {reason}"),
+            Error::Decrypt(reason) => write!(f, "failed to decrypt: {reason}"),
+            Error::UnexpectedType { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            Error::UnexpectedLength {
+                expected,
+                length,
+                found,
+            } => write!(f, "expected {expected} of {length} elements, found {found}"),
+            Error::LimitExceeded(limit) => write!(f, "{limit} limit exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<nom::Err<ParseError>> for Error {
+    fn from(err: nom::Err<ParseError>) -> Self {
+        match err {
+            nom::Err::Incomplete(_) => {
+                Error::Parse(ParseError::with_reason(&[], "unexpected end of input"))
+            }
+            nom::Err::Error(e) | nom::Err::Failure(e) => Error::Parse(e),
+        }
+    }
+}
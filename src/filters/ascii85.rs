@@ -0,0 +1,108 @@
+use crate::error::{Error, Result};
+
+/// Decodes ASCII base-85 data, failing once the output would exceed `limit` bytes. The `z`
+/// abbreviation expands a single character to four zero bytes.
+pub fn ascii85_decode(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut group = [0u8; 5];
+    let mut length = 0usize;
+    for c in data.strip_prefix(b"<~").unwrap_or(data) {
+        match c {
+            b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ' => {}
+            b'~' => break,
+            b'z' if length == 0 => res.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                let digit = group
+                    .get_mut(length)
+                    .ok_or(Error::Decode("invalid ASCII85 group"))?;
+                *digit = c.wrapping_sub(b'!');
+                length = length.saturating_add(1);
+                if length == group.len() {
+                    res.extend_from_slice(&decode_group(group)?);
+                    length = 0;
+                }
+            }
+            _ => return Err(Error::Decode("invalid character in ASCII85 data")),
+        }
+        if res.len() > limit {
+            return Err(Error::LimitExceeded("decoded stream size"));
+        }
+    }
+    match length {
+        0 => {}
+        1 => return Err(Error::Decode("truncated ASCII85 group")),
+        _ => {
+            // A final partial group of n characters is padded with `u` and yields n - 1 bytes.
+            let padded = group
+                .iter()
+                .enumerate()
+                .map(|(i, d)| if i < length { *d } else { 84 })
+                .collect::<Vec<u8>>();
+            let padded: [u8; 5] = padded
+                .try_into()
+                .map_err(|_| Error::Decode("invalid ASCII85 group"))?;
+            let bytes = decode_group(padded)?;
+            res.extend(bytes.iter().take(length.saturating_sub(1)));
+            if res.len() > limit {
+                return Err(Error::LimitExceeded("decoded stream size"));
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn decode_group(group: [u8; 5]) -> Result<[u8; 4]> {
+    let value = group.iter().try_fold(0u32, |acc, digit| {
+        acc.checked_mul(85)?.checked_add(u32::from(*digit))
+    });
+    value
+        .map(u32::to_be_bytes)
+        .ok_or(Error::Decode("ASCII85 group out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn ascii85_1() {
+        assert_eq!(
+            ascii85_decode(b"87cURD]i,\"Ebo80~>", usize::MAX).unwrap(),
+            b"Hello World!"
+        );
+    }
+    #[test]
+    fn ascii85_2() {
+        assert_eq!(
+            ascii85_decode(b"87cUR\nD]i,\"Ebo7~>", usize::MAX).unwrap(),
+            b"Hello World"
+        );
+    }
+    #[test]
+    fn ascii85_3() {
+        assert_eq!(
+            ascii85_decode(b"<~z!!~>", usize::MAX).unwrap(),
+            [0, 0, 0, 0, 0]
+        );
+    }
+    #[test]
+    fn ascii85_4() {
+        assert!(ascii85_decode(b"s8W-\"~>", usize::MAX).is_err());
+        assert!(ascii85_decode(b"87cURv~>", usize::MAX).is_err());
+        assert!(ascii85_decode(b"87cURD~>", usize::MAX).is_err());
+    }
+    #[test]
+    fn ascii85_5() {
+        assert_eq!(
+            ascii85_decode(b"s8W-!~>", usize::MAX).unwrap(),
+            [255, 255, 255, 255]
+        );
+    }
+    #[test]
+    fn ascii85_limit() {
+        assert_eq!(ascii85_decode(b"zz~>", 8).unwrap(), [0; 8]);
+        assert_eq!(
+            ascii85_decode(b"zzz~>", 8).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+}
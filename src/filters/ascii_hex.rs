@@ -0,0 +1,57 @@
+use crate::error::{Error, Result};
+
+pub fn ascii_hex_decode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(data.len().div_ceil(2));
+    let mut high: Option<u8> = None;
+    for c in data {
+        if is_whitespace(*c) {
+            continue;
+        }
+        if *c == b'>' {
+            break;
+        }
+        let digit = hex_value(*c).ok_or(Error::Decode("invalid character in ASCIIHex data"))?;
+        match high.take() {
+            Some(h) => res.push(h.wrapping_shl(4) | digit),
+            None => high = Some(digit),
+        }
+    }
+    if let Some(h) = high {
+        res.push(h.wrapping_shl(4));
+    }
+    Ok(res)
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => c.checked_sub(b'0'),
+        b'a'..=b'f' => c.checked_sub(b'a' - 10),
+        b'A'..=b'F' => c.checked_sub(b'A' - 10),
+        _ => None,
+    }
+}
+
+fn is_whitespace(c: u8) -> bool {
+    b"\0\t\n\x0c\r ".contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn ascii_hex_1() {
+        assert_eq!(ascii_hex_decode(b"48656C6c6F>").unwrap(), b"Hello");
+    }
+    #[test]
+    fn ascii_hex_2() {
+        assert_eq!(ascii_hex_decode(b"4 8\n65 7>ignored").unwrap(), b"Hep");
+    }
+    #[test]
+    fn ascii_hex_3() {
+        assert!(ascii_hex_decode(b"4G>").is_err());
+    }
+    #[test]
+    fn ascii_hex_4() {
+        assert!(ascii_hex_decode(b"").unwrap().is_empty());
+    }
+}
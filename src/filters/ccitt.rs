@@ -0,0 +1,640 @@
+use super::{get_flag, get_param};
+use crate::{
+    error::{Error, Result},
+    objects::Dictionary,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CcittParams {
+    k: i32,
+    columns: usize,
+    rows: usize,
+    encoded_byte_align: bool,
+    black_is_1: bool,
+    end_of_block: bool,
+}
+
+impl Default for CcittParams {
+    fn default() -> Self {
+        Self {
+            k: 0,
+            columns: 1728,
+            rows: 0,
+            encoded_byte_align: false,
+            black_is_1: false,
+            end_of_block: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Pass,
+    Horizontal,
+    Vertical(i8),
+}
+
+impl CcittParams {
+    /// Reads the entries of a `/DecodeParms` dictionary, using the spec defaults for missing
+    /// entries.
+    pub fn new(parms: Option<&Dictionary>) -> Result<Self> {
+        let mut params = Self::default();
+        let Some(parms) = parms else {
+            return Ok(params);
+        };
+        if let Some(k) = get_param(parms, b"K")? {
+            params.k = k;
+        }
+        if let Some(columns) = get_param(parms, b"Columns")? {
+            params.columns = columns;
+        }
+        if let Some(rows) = get_param(parms, b"Rows")? {
+            params.rows = rows;
+        }
+        if let Some(encoded_byte_align) = get_flag(parms, b"EncodedByteAlign")? {
+            params.encoded_byte_align = encoded_byte_align;
+        }
+        if let Some(black_is_1) = get_flag(parms, b"BlackIs1")? {
+            params.black_is_1 = black_is_1;
+        }
+        if let Some(end_of_block) = get_flag(parms, b"EndOfBlock")? {
+            params.end_of_block = end_of_block;
+        }
+        Ok(params)
+    }
+    /// Decodes Group 3 or Group 4 data into rows of packed 1-bit pixels, each row starting on a
+    /// byte boundary. Fails once the output would exceed `limit` bytes.
+    pub fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<u8>> {
+        if self.columns == 0 {
+            return Err(Error::Decode("CCITT image without columns"));
+        }
+        let mut reader = BitReader::new(data);
+        let mut res = Vec::new();
+        let mut reference = Vec::new();
+        let mut row = 0usize;
+        while self.rows == 0 || row < self.rows {
+            if self.encoded_byte_align {
+                reader.align();
+            }
+            let eol = reader.skip_eol();
+            if eol && self.end_of_block && reader.at_end_of_block(self.k) {
+                break;
+            }
+            if reader.only_zeros_left() {
+                break;
+            }
+            let two_dimensional = match self.k {
+                ..0 => true,
+                0 => false,
+                _ => {
+                    reader
+                        .read(1)
+                        .ok_or(Error::Decode("truncated CCITT data"))?
+                        == 0
+                }
+            };
+            let changes = if two_dimensional {
+                self.decode_2d(&mut reader, &reference)?
+            } else {
+                self.decode_1d(&mut reader)?
+            };
+            // Checked before the row is allocated, as `/Columns` is not bounded otherwise.
+            if res.len().saturating_add(self.columns.div_ceil(8)) > limit {
+                return Err(Error::LimitExceeded("decoded stream size"));
+            }
+            self.write_row(&changes, &mut res);
+            reference = changes;
+            row = row.saturating_add(1);
+        }
+        Ok(res)
+    }
+    /// Decodes a row of alternating white and black runs and returns its changing elements.
+    fn decode_1d(&self, reader: &mut BitReader) -> Result<Vec<usize>> {
+        let mut changes = Vec::new();
+        let mut position = 0usize;
+        let mut white = true;
+        while position < self.columns {
+            let run = reader.read_run(white)?;
+            position = position.saturating_add(run).min(self.columns);
+            changes.push(position);
+            white = !white;
+        }
+        Ok(changes)
+    }
+    /// Decodes a row relative to the changing elements of the previous row.
+    fn decode_2d(&self, reader: &mut BitReader, reference: &[usize]) -> Result<Vec<usize>> {
+        let mut changes = Vec::new();
+        let mut a0 = 0usize;
+        let mut start = true;
+        let mut white = true;
+        while a0 < self.columns {
+            let (b1, b2) = self.find_b1_b2(reference, a0, start, white);
+            match reader.read_mode()? {
+                Mode::Pass => a0 = b2,
+                Mode::Horizontal => {
+                    let a1 = a0.saturating_add(reader.read_run(white)?).min(self.columns);
+                    let a2 = a1
+                        .saturating_add(reader.read_run(!white)?)
+                        .min(self.columns);
+                    changes.extend([a1, a2]);
+                    a0 = a2;
+                }
+                Mode::Vertical(offset) => {
+                    let a1 = b1
+                        .checked_add_signed(offset.into())
+                        .filter(|a1| start || *a1 >= a0)
+                        .ok_or(Error::Decode("invalid CCITT vertical mode"))?
+                        .min(self.columns);
+                    changes.push(a1);
+                    a0 = a1;
+                    white = !white;
+                }
+            }
+            start = false;
+        }
+        Ok(changes)
+    }
+    /// Finds the first changing element of the reference row right of `a0` whose color is the
+    /// opposite of the current color, and the changing element following it.
+    fn find_b1_b2(
+        &self,
+        reference: &[usize],
+        a0: usize,
+        start: bool,
+        white: bool,
+    ) -> (usize, usize) {
+        // Changing elements at even positions start black runs.
+        let b1 = reference
+            .iter()
+            .enumerate()
+            .find(|(i, b)| (start || **b > a0) && (i & 1 == 0) == white)
+            .map(|(i, _)| i);
+        let get = |i: Option<usize>| i.and_then(|i| reference.get(i)).copied();
+        let b2 = get(b1.and_then(|i| i.checked_add(1))).unwrap_or(self.columns);
+        (get(b1).unwrap_or(self.columns), b2)
+    }
+    fn write_row(&self, changes: &[usize], res: &mut Vec<u8>) {
+        let white = if self.black_is_1 { 0x00 } else { 0xff };
+        let mut row = vec![white; self.columns.div_ceil(8)];
+        let mut position = 0usize;
+        for (i, change) in changes.iter().enumerate() {
+            if i & 1 == 1 {
+                for x in position..*change {
+                    if let Some(byte) = row.get_mut(x >> 3) {
+                        *byte ^= 0x80u8.wrapping_shr((x & 7).try_into().unwrap_or(0));
+                    }
+                }
+            }
+            position = *change;
+        }
+        res.extend_from_slice(&row);
+    }
+}
+
+#[derive(Debug)]
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+    fn peek(&self, bits: u8) -> Option<u16> {
+        let mut value: u16 = 0;
+        let mut position = self.position;
+        for _ in 0..bits.min(16) {
+            let byte = self.data.get(position >> 3)?;
+            let shift = u32::try_from(7 ^ (position & 7)).ok()?;
+            value = value.wrapping_shl(1) | u16::from(byte.wrapping_shr(shift) & 1);
+            position = position.checked_add(1)?;
+        }
+        Some(value)
+    }
+    fn skip(&mut self, bits: u8) {
+        self.position = self.position.saturating_add(bits.into());
+    }
+    fn read(&mut self, bits: u8) -> Option<u16> {
+        let value = self.peek(bits)?;
+        self.skip(bits);
+        Some(value)
+    }
+    fn align(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+    fn only_zeros_left(&self) -> bool {
+        let mut reader = BitReader::new(self.data);
+        reader.position = self.position;
+        while let Some(bit) = reader.read(1) {
+            if bit != 0 {
+                return false;
+            }
+        }
+        true
+    }
+    /// Skips fill bits and an end-of-line code, if present.
+    fn skip_eol(&mut self) -> bool {
+        let start = self.position;
+        let mut zeros = 0usize;
+        while self.peek(1) == Some(0) {
+            self.skip(1);
+            zeros = zeros.saturating_add(1);
+        }
+        if zeros >= 11 && self.peek(1) == Some(1) {
+            self.skip(1);
+            return true;
+        }
+        self.position = start;
+        false
+    }
+    /// Checks whether a second end-of-line code follows, which makes up the end-of-block of
+    /// Group 4 data or the return-to-control of Group 3 data.
+    fn at_end_of_block(&self, k: i32) -> bool {
+        if k > 0 {
+            self.peek(13) == Some(0b1_0000_0000_0001)
+        } else {
+            self.peek(12) == Some(1)
+        }
+    }
+    fn read_mode(&mut self) -> Result<Mode> {
+        const MODES: [(u16, u8, Mode); 9] = [
+            (0b1, 1, Mode::Vertical(0)),
+            (0b011, 3, Mode::Vertical(1)),
+            (0b010, 3, Mode::Vertical(-1)),
+            (0b001, 3, Mode::Horizontal),
+            (0b0001, 4, Mode::Pass),
+            (0b000011, 6, Mode::Vertical(2)),
+            (0b000010, 6, Mode::Vertical(-2)),
+            (0b0000011, 7, Mode::Vertical(3)),
+            (0b0000010, 7, Mode::Vertical(-3)),
+        ];
+        for (code, length, mode) in MODES {
+            if self.peek(length) == Some(code) {
+                self.skip(length);
+                return Ok(mode);
+            }
+        }
+        Err(Error::Decode("invalid CCITT mode code"))
+    }
+    /// Reads the makeup codes and the terminating code of one run.
+    fn read_run(&mut self, white: bool) -> Result<usize> {
+        let table: &[(u16, u8, u16)] = if white { &WHITE_CODES } else { &BLACK_CODES };
+        let mut run = 0usize;
+        loop {
+            let (_, length, value) = table
+                .iter()
+                .find(|(code, length, _)| self.peek(*length) == Some(*code))
+                .ok_or(Error::Decode("invalid CCITT run length code"))?;
+            self.skip(*length);
+            run = run.saturating_add(usize::from(*value));
+            if *value < 64 {
+                return Ok(run);
+            }
+        }
+    }
+}
+
+const WHITE_CODES: [(u16, u8, u16); 104] = [
+    (0b00110101, 8, 0),
+    (0b000111, 6, 1),
+    (0b0111, 4, 2),
+    (0b1000, 4, 3),
+    (0b1011, 4, 4),
+    (0b1100, 4, 5),
+    (0b1110, 4, 6),
+    (0b1111, 4, 7),
+    (0b10011, 5, 8),
+    (0b10100, 5, 9),
+    (0b00111, 5, 10),
+    (0b01000, 5, 11),
+    (0b001000, 6, 12),
+    (0b000011, 6, 13),
+    (0b110100, 6, 14),
+    (0b110101, 6, 15),
+    (0b101010, 6, 16),
+    (0b101011, 6, 17),
+    (0b0100111, 7, 18),
+    (0b0001100, 7, 19),
+    (0b0001000, 7, 20),
+    (0b0010111, 7, 21),
+    (0b0000011, 7, 22),
+    (0b0000100, 7, 23),
+    (0b0101000, 7, 24),
+    (0b0101011, 7, 25),
+    (0b0010011, 7, 26),
+    (0b0100100, 7, 27),
+    (0b0011000, 7, 28),
+    (0b00000010, 8, 29),
+    (0b00000011, 8, 30),
+    (0b00011010, 8, 31),
+    (0b00011011, 8, 32),
+    (0b00010010, 8, 33),
+    (0b00010011, 8, 34),
+    (0b00010100, 8, 35),
+    (0b00010101, 8, 36),
+    (0b00010110, 8, 37),
+    (0b00010111, 8, 38),
+    (0b00101000, 8, 39),
+    (0b00101001, 8, 40),
+    (0b00101010, 8, 41),
+    (0b00101011, 8, 42),
+    (0b00101100, 8, 43),
+    (0b00101101, 8, 44),
+    (0b00000100, 8, 45),
+    (0b00000101, 8, 46),
+    (0b00001010, 8, 47),
+    (0b00001011, 8, 48),
+    (0b01010010, 8, 49),
+    (0b01010011, 8, 50),
+    (0b01010100, 8, 51),
+    (0b01010101, 8, 52),
+    (0b00100100, 8, 53),
+    (0b00100101, 8, 54),
+    (0b01011000, 8, 55),
+    (0b01011001, 8, 56),
+    (0b01011010, 8, 57),
+    (0b01011011, 8, 58),
+    (0b01001010, 8, 59),
+    (0b01001011, 8, 60),
+    (0b00110010, 8, 61),
+    (0b00110011, 8, 62),
+    (0b00110100, 8, 63),
+    (0b11011, 5, 64),
+    (0b10010, 5, 128),
+    (0b010111, 6, 192),
+    (0b0110111, 7, 256),
+    (0b00110110, 8, 320),
+    (0b00110111, 8, 384),
+    (0b01100100, 8, 448),
+    (0b01100101, 8, 512),
+    (0b01101000, 8, 576),
+    (0b01100111, 8, 640),
+    (0b011001100, 9, 704),
+    (0b011001101, 9, 768),
+    (0b011010010, 9, 832),
+    (0b011010011, 9, 896),
+    (0b011010100, 9, 960),
+    (0b011010101, 9, 1024),
+    (0b011010110, 9, 1088),
+    (0b011010111, 9, 1152),
+    (0b011011000, 9, 1216),
+    (0b011011001, 9, 1280),
+    (0b011011010, 9, 1344),
+    (0b011011011, 9, 1408),
+    (0b010011000, 9, 1472),
+    (0b010011001, 9, 1536),
+    (0b010011010, 9, 1600),
+    (0b011000, 6, 1664),
+    (0b010011011, 9, 1728),
+    (0b00000001000, 11, 1792),
+    (0b00000001100, 11, 1856),
+    (0b00000001101, 11, 1920),
+    (0b000000010010, 12, 1984),
+    (0b000000010011, 12, 2048),
+    (0b000000010100, 12, 2112),
+    (0b000000010101, 12, 2176),
+    (0b000000010110, 12, 2240),
+    (0b000000010111, 12, 2304),
+    (0b000000011100, 12, 2368),
+    (0b000000011101, 12, 2432),
+    (0b000000011110, 12, 2496),
+    (0b000000011111, 12, 2560),
+];
+const BLACK_CODES: [(u16, u8, u16); 104] = [
+    (0b0000110111, 10, 0),
+    (0b010, 3, 1),
+    (0b11, 2, 2),
+    (0b10, 2, 3),
+    (0b011, 3, 4),
+    (0b0011, 4, 5),
+    (0b0010, 4, 6),
+    (0b00011, 5, 7),
+    (0b000101, 6, 8),
+    (0b000100, 6, 9),
+    (0b0000100, 7, 10),
+    (0b0000101, 7, 11),
+    (0b0000111, 7, 12),
+    (0b00000100, 8, 13),
+    (0b00000111, 8, 14),
+    (0b000011000, 9, 15),
+    (0b0000010111, 10, 16),
+    (0b0000011000, 10, 17),
+    (0b0000001000, 10, 18),
+    (0b00001100111, 11, 19),
+    (0b00001101000, 11, 20),
+    (0b00001101100, 11, 21),
+    (0b00000110111, 11, 22),
+    (0b00000101000, 11, 23),
+    (0b00000010111, 11, 24),
+    (0b00000011000, 11, 25),
+    (0b000011001010, 12, 26),
+    (0b000011001011, 12, 27),
+    (0b000011001100, 12, 28),
+    (0b000011001101, 12, 29),
+    (0b000001101000, 12, 30),
+    (0b000001101001, 12, 31),
+    (0b000001101010, 12, 32),
+    (0b000001101011, 12, 33),
+    (0b000011010010, 12, 34),
+    (0b000011010011, 12, 35),
+    (0b000011010100, 12, 36),
+    (0b000011010101, 12, 37),
+    (0b000011010110, 12, 38),
+    (0b000011010111, 12, 39),
+    (0b000001101100, 12, 40),
+    (0b000001101101, 12, 41),
+    (0b000011011010, 12, 42),
+    (0b000011011011, 12, 43),
+    (0b000001010100, 12, 44),
+    (0b000001010101, 12, 45),
+    (0b000001010110, 12, 46),
+    (0b000001010111, 12, 47),
+    (0b000001100100, 12, 48),
+    (0b000001100101, 12, 49),
+    (0b000001010010, 12, 50),
+    (0b000001010011, 12, 51),
+    (0b000000100100, 12, 52),
+    (0b000000110111, 12, 53),
+    (0b000000111000, 12, 54),
+    (0b000000100111, 12, 55),
+    (0b000000101000, 12, 56),
+    (0b000001011000, 12, 57),
+    (0b000001011001, 12, 58),
+    (0b000000101011, 12, 59),
+    (0b000000101100, 12, 60),
+    (0b000001011010, 12, 61),
+    (0b000001100110, 12, 62),
+    (0b000001100111, 12, 63),
+    (0b0000001111, 10, 64),
+    (0b000011001000, 12, 128),
+    (0b000011001001, 12, 192),
+    (0b000001011011, 12, 256),
+    (0b000000110011, 12, 320),
+    (0b000000110100, 12, 384),
+    (0b000000110101, 12, 448),
+    (0b0000001101100, 13, 512),
+    (0b0000001101101, 13, 576),
+    (0b0000001001010, 13, 640),
+    (0b0000001001011, 13, 704),
+    (0b0000001001100, 13, 768),
+    (0b0000001001101, 13, 832),
+    (0b0000001110010, 13, 896),
+    (0b0000001110011, 13, 960),
+    (0b0000001110100, 13, 1024),
+    (0b0000001110101, 13, 1088),
+    (0b0000001110110, 13, 1152),
+    (0b0000001110111, 13, 1216),
+    (0b0000001010010, 13, 1280),
+    (0b0000001010011, 13, 1344),
+    (0b0000001010100, 13, 1408),
+    (0b0000001010101, 13, 1472),
+    (0b0000001011010, 13, 1536),
+    (0b0000001011011, 13, 1600),
+    (0b0000001100100, 13, 1664),
+    (0b0000001100101, 13, 1728),
+    (0b00000001000, 11, 1792),
+    (0b00000001100, 11, 1856),
+    (0b00000001101, 11, 1920),
+    (0b000000010010, 12, 1984),
+    (0b000000010011, 12, 2048),
+    (0b000000010100, 12, 2112),
+    (0b000000010101, 12, 2176),
+    (0b000000010110, 12, 2240),
+    (0b000000010111, 12, 2304),
+    (0b000000011100, 12, 2368),
+    (0b000000011101, 12, 2432),
+    (0b000000011110, 12, 2496),
+    (0b000000011111, 12, 2560),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four rows of 20 pixels encoded by an independent T.4/T.6 encoder.
+    const PACKED: [u8; 12] = [
+        0xff, 0xff, 0xff, 0xe1, 0xf3, 0xef, 0xc0, 0xf9, 0xcf, 0x07, 0xff, 0xff,
+    ];
+    fn params(k: i32) -> CcittParams {
+        CcittParams {
+            k,
+            columns: 20,
+            ..Default::default()
+        }
+    }
+    #[test]
+    fn group_4() {
+        let data = [
+            0x98, 0x67, 0x35, 0x4d, 0xb5, 0x08, 0x10, 0x86, 0x00, 0x20, 0x02,
+        ];
+        assert_eq!(params(-1).decode(&data, usize::MAX).unwrap(), PACKED);
+    }
+    #[test]
+    fn group_4_black_is_1() {
+        let data = [
+            0x98, 0x67, 0x35, 0x4d, 0xb5, 0x08, 0x10, 0x86, 0x00, 0x20, 0x02,
+        ];
+        let mut p = params(-1);
+        p.black_is_1 = true;
+        let inverted: Vec<u8> = PACKED.iter().map(|b| !b).collect();
+        assert_eq!(p.decode(&data, usize::MAX).unwrap(), inverted);
+    }
+    #[test]
+    fn group_4_byte_align() {
+        let data = [0x80, 0x30, 0xce, 0x6a, 0x4d, 0xb5, 0x08, 0x10, 0x86];
+        let mut p = params(-1);
+        p.encoded_byte_align = true;
+        p.end_of_block = false;
+        p.rows = 4;
+        assert_eq!(p.decode(&data, usize::MAX).unwrap(), PACKED);
+    }
+    #[test]
+    fn group_3_1d() {
+        let data = [
+            0x00, 0x11, 0x00, 0x03, 0x0f, 0x3c, 0x40, 0x02, 0xe5, 0x9c, 0x60, 0x02, 0x6a, 0x7a,
+            0x80, 0x08, 0x00, 0x80, 0x08, 0x00, 0x80, 0x08, 0x00, 0x80,
+        ];
+        assert_eq!(params(0).decode(&data, usize::MAX).unwrap(), PACKED);
+    }
+    #[test]
+    fn group_3_1d_without_eol() {
+        let data = [0x11, 0x0f, 0x3c, 0x4e, 0x59, 0xc6, 0x6a, 0x7a, 0x80];
+        let mut p = params(0);
+        p.end_of_block = false;
+        p.rows = 4;
+        assert_eq!(p.decode(&data, usize::MAX).unwrap(), PACKED);
+    }
+    #[test]
+    fn group_3_2d() {
+        let data = [
+            0x00, 0x18, 0x80, 0x01, 0x18, 0x67, 0x35, 0x00, 0x1b, 0x96, 0x71, 0x80, 0x08, 0x20,
+            0x42, 0x18, 0x00, 0xc0, 0x06, 0x00, 0x30, 0x01, 0x80, 0x0c, 0x00, 0x60,
+        ];
+        assert_eq!(params(2).decode(&data, usize::MAX).unwrap(), PACKED);
+    }
+    #[test]
+    fn group_4_makeup_codes() {
+        let data = [
+            0x3b, 0x15, 0x03, 0x20, 0x6a, 0x49, 0xa8, 0x0f, 0x30, 0x01, 0x00, 0x10,
+        ];
+        let p = CcittParams {
+            k: -1,
+            columns: 2500,
+            ..Default::default()
+        };
+        let decoded = p.decode(&data, usize::MAX).unwrap();
+        assert_eq!(decoded.len(), 626);
+        // 100 white, 1700 black and 700 white pixels, then an all black row.
+        assert_eq!(decoded[11], 0xff);
+        assert_eq!(decoded[12], 0xf0);
+        assert_eq!(decoded[13], 0x00);
+        assert_eq!(decoded[224], 0x00);
+        assert_eq!(decoded[225], 0xff);
+        assert_eq!(decoded[312], 0xff);
+        assert!(decoded[313..625].iter().all(|b| *b == 0));
+        assert_eq!(decoded[625], 0x0f);
+    }
+    #[test]
+    fn limit_exceeded() {
+        let data = [
+            0x98, 0x67, 0x35, 0x4d, 0xb5, 0x08, 0x10, 0x86, 0x00, 0x20, 0x02,
+        ];
+        assert_eq!(
+            params(-1).decode(&data, 11).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+        let p = CcittParams {
+            k: -1,
+            columns: usize::MAX >> 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            p.decode(&[0xff], 256 << 20).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+    #[test]
+    fn invalid_code() {
+        assert!(params(-1).decode(&[0x02, 0xff], usize::MAX).is_err());
+    }
+    #[test]
+    fn params_from_dictionary() {
+        let (_, dict) = Dictionary::parse(
+            b"<</K -1 /Columns 2480 /Rows 3508 /BlackIs1 true /EncodedByteAlign false>>",
+        )
+        .unwrap();
+        let parsed = CcittParams::new(Some(&dict)).unwrap();
+        assert_eq!(
+            parsed,
+            CcittParams {
+                k: -1,
+                columns: 2480,
+                rows: 3508,
+                encoded_byte_align: false,
+                black_is_1: true,
+                end_of_block: true,
+            }
+        );
+    }
+}
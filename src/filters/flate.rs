@@ -0,0 +1,38 @@
+use miniz_oxide::inflate::{TINFLStatus, decompress_to_vec_zlib_with_limit};
+
+use crate::error::{Error, Result};
+
+/// Inflates zlib data, failing once the output would exceed `limit` bytes.
+pub fn flate_decode(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    decompress_to_vec_zlib_with_limit(data, limit).map_err(|e| match e.status {
+        TINFLStatus::HasMoreOutput => Error::LimitExceeded("decoded stream size"),
+        _ => Error::Decode("corrupt deflate data"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn flate_decode_1() {
+        let data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x15,
+        ];
+        assert_eq!(flate_decode(&data, usize::MAX).unwrap(), b"hello");
+    }
+    #[test]
+    fn flate_decode_2() {
+        assert!(flate_decode(b"hello", usize::MAX).is_err());
+    }
+    #[test]
+    fn flate_decode_limit() {
+        let data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x15,
+        ];
+        assert_eq!(flate_decode(&data, 5).unwrap(), b"hello");
+        assert_eq!(
+            flate_decode(&data, 4).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+}
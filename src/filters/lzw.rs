@@ -0,0 +1,210 @@
+use crate::error::{Error, Result};
+
+const CLEAR_TABLE: u16 = 256;
+const END_OF_DATA: u16 = 257;
+const MAX_ENTRIES: usize = 4096;
+
+/// Decodes LZW data; with `early_change` the code width grows one code earlier, as TIFF does.
+/// Fails once the output would exceed `limit` bytes.
+pub fn lzw_decode(data: &[u8], early_change: bool, limit: usize) -> Result<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut table = initial_table();
+    let mut res = Vec::with_capacity(data.len().saturating_mul(2));
+    let mut prev: Option<Vec<u8>> = None;
+    loop {
+        let width = code_width(table.len(), early_change);
+        let Some(code) = reader.read(width) else {
+            break;
+        };
+        match code {
+            CLEAR_TABLE => {
+                table = initial_table();
+                prev = None;
+                continue;
+            }
+            END_OF_DATA => break,
+            _ => {}
+        }
+        let entry = match (table.get(usize::from(code)), &prev) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(prev)) if usize::from(code) == table.len() => {
+                let mut entry = prev.clone();
+                entry.extend(prev.first());
+                entry
+            }
+            _ => return Err(Error::Decode("invalid LZW code")),
+        };
+        if res.len().saturating_add(entry.len()) > limit {
+            return Err(Error::LimitExceeded("decoded stream size"));
+        }
+        res.extend_from_slice(&entry);
+        if let Some(mut prev) = prev.take()
+            && table.len() < MAX_ENTRIES
+        {
+            prev.extend(entry.first());
+            table.push(prev);
+        }
+        prev = Some(entry);
+    }
+    Ok(res)
+}
+
+fn initial_table() -> Vec<Vec<u8>> {
+    // The two placeholders stand for the clear-table and end-of-data codes.
+    let mut table: Vec<Vec<u8>> = (0..=u8::MAX).map(|b| vec![b]).collect();
+    table.extend([Vec::new(), Vec::new()]);
+    table
+}
+
+fn code_width(entries: usize, early_change: bool) -> u8 {
+    match entries.saturating_add(usize::from(early_change)) {
+        ..512 => 9,
+        512..1024 => 10,
+        1024..2048 => 11,
+        _ => 12,
+    }
+}
+
+/// Reads most significant bit first codes of up to 16 bits.
+#[derive(Debug)]
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+    fn read(&mut self, bits: u8) -> Option<u16> {
+        if bits > 16 {
+            return None;
+        }
+        let mut value: u32 = 0;
+        for _ in 0..bits {
+            let byte = self.data.get(self.position >> 3)?;
+            let shift = u32::try_from(7 ^ (self.position & 7)).ok()?;
+            value = value.wrapping_shl(1) | u32::from(byte.wrapping_shr(shift) & 1);
+            self.position = self.position.checked_add(1)?;
+        }
+        u16::try_from(value).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs codes most significant bit first with the widths the decoder expects.
+    fn encode(codes: &[u16], early_change: bool) -> Vec<u8> {
+        let mut bits = Vec::new();
+        let mut entries = 258usize;
+        let mut first = true;
+        for code in codes {
+            let width = code_width(entries, early_change);
+            for i in (0..width).rev() {
+                bits.push(code >> i & 1 == 1);
+            }
+            if *code == CLEAR_TABLE {
+                entries = 258;
+                first = true;
+            } else if first {
+                first = false;
+            } else {
+                entries = entries.saturating_add(1);
+            }
+        }
+        bits.chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .chain(core::iter::repeat(&false))
+                    .take(8)
+                    .fold(0u8, |acc, b| acc.wrapping_shl(1) | u8::from(*b))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lzw_spec_example() {
+        // The example of the PDF specification, section 7.4.4.2.
+        let data = [0x80, 0x0b, 0x60, 0x50, 0x22, 0x0c, 0x0c, 0x85, 0x01];
+        assert_eq!(
+            lzw_decode(&data, true, usize::MAX).unwrap(),
+            [45, 45, 45, 45, 45, 65, 45, 45, 45, 66]
+        );
+    }
+    #[test]
+    fn lzw_k_omega_k() {
+        let data = encode(&[CLEAR_TABLE, 97, 258, 259, END_OF_DATA], true);
+        assert_eq!(lzw_decode(&data, true, usize::MAX).unwrap(), b"aaaaaa");
+    }
+    #[test]
+    fn lzw_code_width_change() {
+        let mut codes = vec![CLEAR_TABLE];
+        codes.extend((0..300).map(|i| i % 256));
+        codes.push(END_OF_DATA);
+        let expected: Vec<u8> = (0..300u16)
+            .map(|i| u8::try_from(i % 256).unwrap())
+            .collect();
+        for early_change in [true, false] {
+            let data = encode(&codes, early_change);
+            assert_eq!(
+                lzw_decode(&data, early_change, usize::MAX).unwrap(),
+                expected
+            );
+        }
+    }
+    #[test]
+    fn lzw_invalid_code() {
+        let data = encode(&[CLEAR_TABLE, 97, 300], true);
+        assert!(lzw_decode(&data, true, usize::MAX).is_err());
+    }
+    #[test]
+    fn lzw_limit() {
+        let data = encode(&[CLEAR_TABLE, 97, 258, 259, END_OF_DATA], true);
+        assert_eq!(lzw_decode(&data, true, 6).unwrap(), b"aaaaaa");
+        assert_eq!(
+            lzw_decode(&data, true, 5).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+    #[test]
+    fn bit_reader() {
+        let mut reader = BitReader::new(&[0b1010_1100, 0b0011_1111]);
+        assert_eq!(reader.read(3), Some(0b101));
+        assert_eq!(reader.read(9), Some(0b0_1100_0011));
+        assert_eq!(reader.read(4), Some(0b1111));
+        assert_eq!(reader.read(1), None);
+    }
+}
+
+#[cfg(kani)]
+#[kani::proof]
+#[kani::unwind(18)]
+fn bit_reader_value_fits_width() {
+    let data: [u8; 3] = kani::any();
+    let bits: u8 = kani::any();
+    kani::assume(bits <= 16);
+    let mut reader = BitReader::new(&data);
+    if let Some(value) = reader.read(bits) {
+        assert!(u32::from(value) < 1u32 << bits);
+    }
+}
+#[cfg(kani)]
+#[kani::proof]
+#[kani::unwind(18)]
+fn bit_reader_stops_at_end() {
+    let data: [u8; 2] = kani::any();
+    let first: u8 = kani::any();
+    let second: u8 = kani::any();
+    kani::assume(first <= 16 && second <= 16);
+    let mut reader = BitReader::new(&data);
+    let a = reader.read(first);
+    let b = reader.read(second);
+    if u32::from(first) + u32::from(second) > 16 {
+        assert!(a.is_none() || b.is_none());
+    } else {
+        assert!(a.is_some() && b.is_some());
+    }
+}
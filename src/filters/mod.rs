@@ -0,0 +1,236 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod ascii85;
+mod ascii_hex;
+mod ccitt;
+mod flate;
+mod lzw;
+mod predictor;
+mod run_length;
+pub use ascii_hex::*;
+pub use ascii85::*;
+pub use ccitt::*;
+pub use flate::*;
+pub use lzw::*;
+pub use predictor::*;
+pub use run_length::*;
+
+use crate::{
+    error::{Error, Result},
+    objects::{Array, Boolean, Dictionary, GetObj, Integer, Name, Object},
+};
+type RString = std::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    AsciiHexDecode,
+    Ascii85Decode,
+    LzwDecode,
+    FlateDecode,
+    RunLengthDecode,
+    CcittFaxDecode,
+}
+
+impl Filter {
+    /// Looks up a filter by its name, accepting the abbreviations used by inline images.
+    pub fn from_name(name: &Name) -> Result<Filter> {
+        match name.get() {
+            b"ASCIIHexDecode" | b"AHx" => Ok(Filter::AsciiHexDecode),
+            b"ASCII85Decode" | b"A85" => Ok(Filter::Ascii85Decode),
+            b"LZWDecode" | b"LZW" => Ok(Filter::LzwDecode),
+            b"FlateDecode" | b"Fl" => Ok(Filter::FlateDecode),
+            b"RunLengthDecode" | b"RL" => Ok(Filter::RunLengthDecode),
+            b"CCITTFaxDecode" | b"CCF" => Ok(Filter::CcittFaxDecode),
+            other => Err(Error::UnsupportedFilter(
+                RString::from_utf8_lossy(other).into_owned(),
+            )),
+        }
+    }
+    pub fn decode(&self, data: &[u8], parms: Option<&Dictionary>) -> Result<Vec<u8>> {
+        self.decode_limited(data, parms, usize::MAX)
+    }
+    /// Decodes `data`, failing with [`Error::LimitExceeded`] once the output would exceed `limit`
+    /// bytes. ASCIIHexDecode is the only filter that cannot expand its input and is not checked.
+    pub fn decode_limited(
+        &self,
+        data: &[u8],
+        parms: Option<&Dictionary>,
+        limit: usize,
+    ) -> Result<Vec<u8>> {
+        match self {
+            Filter::AsciiHexDecode => ascii_hex_decode(data),
+            Filter::Ascii85Decode => ascii85_decode(data, limit),
+            Filter::LzwDecode => {
+                let early_change = early_change(parms)?;
+                let data = lzw_decode(data, early_change, limit)?;
+                PredictorParams::new(parms)?.decode(data, limit)
+            }
+            Filter::FlateDecode => {
+                PredictorParams::new(parms)?.decode(flate_decode(data, limit)?, limit)
+            }
+            Filter::RunLengthDecode => run_length_decode(data, limit),
+            Filter::CcittFaxDecode => CcittParams::new(parms)?.decode(data, limit),
+        }
+    }
+}
+
+fn early_change(parms: Option<&Dictionary>) -> Result<bool> {
+    let Some(value) = parms.and_then(|p| p.get(&Name::new(b"EarlyChange"))) else {
+        return Ok(true);
+    };
+    let integer: Option<&Integer> = value.get_obj();
+    if let Some(integer) = integer {
+        return Ok(integer.get() != 0);
+    }
+    let boolean: Option<&Boolean> = value.get_obj();
+    if let Some(boolean) = boolean {
+        return Ok(boolean.get());
+    }
+    Err(Error::Decode("EarlyChange is not an integer"))
+}
+
+/// Pairs every entry of the `/Filter` of a stream dictionary with its `/DecodeParms`, both of
+/// which may be a single value or an array.
+pub fn filter_chain<'a, 'b>(
+    info: &'a Dictionary<'b>,
+) -> Result<Vec<(Filter, Option<&'a Dictionary<'b>>)>> {
+    let filters = match info.get(&Name::new(b"Filter")) {
+        None | Some(Object::Null(_)) => return Ok(Vec::new()),
+        Some(Object::Name(name)) => vec![name],
+        Some(Object::Array(array)) => array
+            .get()
+            .iter()
+            .map(|f| f.get_obj().ok_or(Error::Decode("filter is not a name")))
+            .collect::<Result<Vec<&Name>>>()?,
+        Some(_) => return Err(Error::Decode("filter is not a name")),
+    };
+    let parms = info.get(&Name::new(b"DecodeParms"));
+    filters
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let parms = match parms {
+                Some(Object::Dictionary(dict)) if i == 0 => Some(dict),
+                Some(Object::Array(array)) => get_dictionary(array, i),
+                _ => None,
+            };
+            Ok((Filter::from_name(name)?, parms))
+        })
+        .collect()
+}
+
+fn get_param<T: TryFrom<i64>>(parms: &Dictionary, key: &'static [u8]) -> Result<Option<T>> {
+    let Some(value) = parms.get(&Name::new(key)) else {
+        return Ok(None);
+    };
+    let value: &Integer = value
+        .get_obj()
+        .ok_or(Error::Decode("decode parameter is not an integer"))?;
+    let value = value
+        .get()
+        .try_into()
+        .map_err(|_| Error::Decode("decode parameter out of range"))?;
+    Ok(Some(value))
+}
+
+fn get_flag(parms: &Dictionary, key: &'static [u8]) -> Result<Option<bool>> {
+    let Some(value) = parms.get(&Name::new(key)) else {
+        return Ok(None);
+    };
+    let value: &Boolean = value
+        .get_obj()
+        .ok_or(Error::Decode("decode parameter is not a boolean"))?;
+    Ok(Some(value.get()))
+}
+
+fn get_dictionary<'a, 'b>(array: &'a Array<'b>, i: usize) -> Option<&'a Dictionary<'b>> {
+    array.get().get(i)?.get_obj()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{limits::Limits, objects::Stream};
+    #[test]
+    fn filter_chain_1() {
+        let (_, dict) = Dictionary::parse(b"<</Length 3>>").unwrap();
+        assert!(filter_chain(&dict).unwrap().is_empty());
+    }
+    #[test]
+    fn filter_chain_2() {
+        let (_, dict) =
+            Dictionary::parse(b"<</Filter /FlateDecode /DecodeParms <</Predictor 12>>>>").unwrap();
+        let chain = filter_chain(&dict).unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].0, Filter::FlateDecode);
+        assert!(chain[0].1.is_some());
+    }
+    #[test]
+    fn filter_chain_3() {
+        let (_, dict) =
+            Dictionary::parse(b"<</Filter [/Fl /FlateDecode] /DecodeParms [null <</Columns 4>>]>>")
+                .unwrap();
+        let chain = filter_chain(&dict).unwrap();
+        assert_eq!(chain.len(), 2);
+        assert!(chain[0].1.is_none());
+        assert!(chain[1].1.is_some());
+    }
+    #[test]
+    fn filter_chain_4() {
+        let (_, dict) = Dictionary::parse(b"<</Filter /JBIG2Decode>>").unwrap();
+        assert_eq!(
+            filter_chain(&dict).unwrap_err(),
+            Error::UnsupportedFilter("JBIG2Decode".into())
+        );
+    }
+    #[test]
+    fn decode_chain() {
+        let input = b"<</Length 15 /Filter [/AHx /RL]>>stream\n02616263FD7880>\nendstream";
+        let (_, stream) = crate::objects::Stream::parse(input).unwrap();
+        assert_eq!(stream.decode().unwrap(), b"abcxxxx");
+    }
+    #[test]
+    fn decode_limited_hostile() {
+        let decode = |info: &[u8], data: Vec<u8>, limits: &Limits| {
+            let (_, info) = Dictionary::parse(info).unwrap();
+            Stream::new(info, data).unwrap().decode_limited(limits)
+        };
+        let exceeded = Err(Error::LimitExceeded("decoded stream size"));
+        let defaults = Limits::default();
+        let bomb = miniz_oxide::deflate::compress_to_vec_zlib(&[0; 4 << 20], 6);
+        assert_eq!(decode(b"<</Filter /Fl>>", bomb, &defaults), exceeded);
+        let flate = miniz_oxide::deflate::compress_to_vec_zlib(&[2, 1, 2], 6);
+        let png = b"<</Filter /Fl /DecodeParms <</Predictor 12 /Columns 288230376151711744>>>>";
+        assert_eq!(decode(png, flate, &defaults), exceeded);
+        // The LZW codes for a clear code, `a` and the end of data.
+        let lzw = vec![0x80, 0x18, 0x60, 0x20];
+        let tiff = b"<</Filter /LZW /DecodeParms <</Predictor 2 /Columns 288230376151711744>>>>";
+        assert_eq!(decode(tiff, lzw, &defaults), exceeded);
+        let ccitt = b"<</Filter /CCF /DecodeParms <</K -1 /Columns 9223372036854775807>>>>";
+        assert_eq!(decode(ccitt, vec![0xff], &defaults), exceeded);
+        // ASCII85 and run-length data expand too little to exceed the default limits.
+        let small = Limits {
+            max_decoded_size: 64,
+            ..defaults
+        };
+        assert_eq!(
+            decode(b"<</Filter /A85>>", vec![b'z'; 17], &small),
+            exceeded
+        );
+        assert_eq!(decode(b"<</Filter /RL>>", vec![129, 0], &small), exceeded);
+    }
+    #[test]
+    fn decode_lzw_early_change() {
+        let (_, dict) = Dictionary::parse(b"<</EarlyChange 0>>").unwrap();
+        assert!(!early_change(Some(&dict)).unwrap());
+        assert!(early_change(None).unwrap());
+    }
+    #[test]
+    fn filter_chain_5() {
+        let (_, dict) = Dictionary::parse(b"<</Filter 5>>").unwrap();
+        assert!(filter_chain(&dict).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use super::get_param;
+use crate::{
+    error::{Error, Result},
+    objects::Dictionary,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PredictorParams {
+    predictor: u8,
+    colors: usize,
+    bits_per_component: usize,
+    columns: usize,
+}
+
+impl Default for PredictorParams {
+    fn default() -> Self {
+        Self {
+            predictor: 1,
+            colors: 1,
+            bits_per_component: 8,
+            columns: 1,
+        }
+    }
+}
+
+impl PredictorParams {
+    /// Reads the predictor entries of a `/DecodeParms` dictionary, using the spec defaults
+    /// for missing entries.
+    pub fn new(parms: Option<&Dictionary>) -> Result<Self> {
+        let mut params = Self::default();
+        let Some(parms) = parms else {
+            return Ok(params);
+        };
+        if let Some(predictor) = get_param(parms, b"Predictor")? {
+            params.predictor = predictor;
+        }
+        if let Some(colors) = get_param(parms, b"Colors")? {
+            params.colors = colors;
+        }
+        if let Some(bits_per_component) = get_param(parms, b"BitsPerComponent")? {
+            params.bits_per_component = bits_per_component;
+        }
+        if let Some(columns) = get_param(parms, b"Columns")? {
+            params.columns = columns;
+        }
+        Ok(params)
+    }
+    /// Undoes the prediction of `data`, failing once the output would exceed `limit` bytes.
+    pub fn decode(&self, data: Vec<u8>, limit: usize) -> Result<Vec<u8>> {
+        match self.predictor {
+            1 => Ok(data),
+            2 | 10..=15 if data.is_empty() => Ok(data),
+            2 => self.decode_tiff(data, limit),
+            10..=15 => self.decode_png(&data, limit),
+            _ => Err(Error::Decode("unsupported predictor")),
+        }
+    }
+    fn bits_per_pixel(&self) -> Result<usize> {
+        self.colors
+            .checked_mul(self.bits_per_component)
+            .ok_or(Error::Decode("predictor row too large"))
+    }
+    fn bytes_per_pixel(&self) -> Result<usize> {
+        Ok(self.bits_per_pixel()?.div_ceil(8).max(1))
+    }
+    fn bytes_per_row(&self) -> Result<usize> {
+        Ok(self
+            .bits_per_pixel()?
+            .checked_mul(self.columns)
+            .ok_or(Error::Decode("predictor row too large"))?
+            .div_ceil(8))
+    }
+    /// The length of a row, which may not exceed the data since only the last row may be short.
+    fn row_length(&self, data_length: usize, limit: usize) -> Result<usize> {
+        let row_length = self.bytes_per_row()?;
+        if row_length > limit {
+            return Err(Error::LimitExceeded("decoded stream size"));
+        }
+        if row_length > data_length {
+            return Err(Error::Decode("predictor row longer than the data"));
+        }
+        Ok(row_length)
+    }
+    fn decode_tiff(&self, mut data: Vec<u8>, limit: usize) -> Result<Vec<u8>> {
+        if !matches!(self.bits_per_component, 1 | 2 | 4 | 8 | 16) {
+            return Err(Error::Decode("unsupported bits per component"));
+        }
+        let row_length = self.row_length(data.len(), limit)?;
+        if row_length == 0 {
+            return Ok(data);
+        }
+        let samples = self
+            .columns
+            .checked_mul(self.colors)
+            .ok_or(Error::Decode("predictor row too large"))?;
+        for row in data.chunks_mut(row_length) {
+            // Only the samples present in a truncated last row are decoded.
+            let present = row
+                .len()
+                .saturating_mul(8)
+                .checked_div(self.bits_per_component);
+            for i in self.colors..samples.min(present.unwrap_or(0)) {
+                let Some(left) = i.checked_sub(self.colors) else {
+                    continue;
+                };
+                let left = get_sample(row, left, self.bits_per_component);
+                let current = get_sample(row, i, self.bits_per_component);
+                set_sample(row, i, self.bits_per_component, current.wrapping_add(left));
+            }
+        }
+        Ok(data)
+    }
+    fn decode_png(&self, data: &[u8], limit: usize) -> Result<Vec<u8>> {
+        let bpp = self.bytes_per_pixel()?;
+        let row_length = self.row_length(data.len(), limit)?;
+        let mut res = Vec::with_capacity(data.len());
+        let mut prev = vec![0u8; row_length];
+        for chunk in data.chunks(row_length.saturating_add(1)) {
+            let Some((&filter, row)) = chunk.split_first() else {
+                continue;
+            };
+            let mut current = row.to_vec();
+            current.resize(row_length, 0);
+            for i in 0..row_length {
+                let left = i.checked_sub(bpp).and_then(|j| current.get(j)).copied();
+                let left = left.unwrap_or(0);
+                let up = prev.get(i).copied().unwrap_or(0);
+                let up_left = i.checked_sub(bpp).and_then(|j| prev.get(j)).copied();
+                let up_left = up_left.unwrap_or(0);
+                let predicted = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => left.midpoint(up),
+                    4 => paeth(left, up, up_left),
+                    _ => return Err(Error::Decode("invalid png predictor row")),
+                };
+                if let Some(byte) = current.get_mut(i) {
+                    *byte = byte.wrapping_add(predicted);
+                }
+            }
+            // A short last row is padded, which may take the output past the limit.
+            if res.len().saturating_add(row_length) > limit {
+                return Err(Error::LimitExceeded("decoded stream size"));
+            }
+            res.extend_from_slice(&current);
+            prev = current;
+        }
+        Ok(res)
+    }
+}
+
+/// Reads sample `index` of a row of `bpc`-bit samples, the missing bits of a truncated row are 0.
+fn get_sample(row: &[u8], index: usize, bpc: usize) -> u16 {
+    let bit = index.saturating_mul(bpc);
+    let byte = |i: usize| row.get(i).copied().map_or(0, u16::from);
+    match bpc {
+        16 => byte(bit >> 3).wrapping_shl(8) | byte((bit >> 3).saturating_add(1)),
+        _ => {
+            let shift = 8usize.saturating_sub(bpc).saturating_sub(bit & 7);
+            let mask = 1u16
+                .wrapping_shl(bpc.try_into().unwrap_or(0))
+                .wrapping_sub(1);
+            byte(bit >> 3).wrapping_shr(shift.try_into().unwrap_or(0)) & mask
+        }
+    }
+}
+
+/// Stores the low `bpc` bits of `value` as sample `index` of a row.
+fn set_sample(row: &mut [u8], index: usize, bpc: usize, value: u16) {
+    let bit = index.saturating_mul(bpc);
+    let [high, low] = value.to_be_bytes();
+    match bpc {
+        16 => {
+            if let Some(b) = row.get_mut(bit >> 3) {
+                *b = high;
+            }
+            if let Some(b) = row.get_mut((bit >> 3).saturating_add(1)) {
+                *b = low;
+            }
+        }
+        _ => {
+            let shift = 8usize.saturating_sub(bpc).saturating_sub(bit & 7);
+            let shift = shift.try_into().unwrap_or(0);
+            let mask = 1u8
+                .checked_shl(bpc.try_into().unwrap_or(0))
+                .unwrap_or(0)
+                .wrapping_sub(1)
+                .wrapping_shl(shift);
+            if let Some(b) = row.get_mut(bit >> 3) {
+                *b = (*b & !mask) | (low.wrapping_shl(shift) & mask);
+            }
+        }
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a)
+        .wrapping_add(i16::from(b))
+        .wrapping_sub(i16::from(c));
+    let pa = p.wrapping_sub(i16::from(a)).unsigned_abs();
+    let pb = p.wrapping_sub(i16::from(b)).unsigned_abs();
+    let pc = p.wrapping_sub(i16::from(c)).unsigned_abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn params(predictor: u8, colors: usize, columns: usize) -> PredictorParams {
+        PredictorParams {
+            predictor,
+            colors,
+            bits_per_component: 8,
+            columns,
+        }
+    }
+    #[test]
+    fn png_none() {
+        let decoded = params(10, 1, 3)
+            .decode(vec![0, 1, 2, 3, 0, 4, 5, 6], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [1, 2, 3, 4, 5, 6]);
+    }
+    #[test]
+    fn png_sub() {
+        let decoded = params(11, 1, 3)
+            .decode(vec![1, 1, 1, 1], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [1, 2, 3]);
+    }
+    #[test]
+    fn png_up() {
+        let decoded = params(12, 1, 2)
+            .decode(vec![2, 1, 2, 2, 1, 1, 2, 255, 0], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [1, 2, 2, 3, 1, 3]);
+    }
+    #[test]
+    fn png_average() {
+        let decoded = params(13, 1, 2)
+            .decode(vec![0, 4, 6, 3, 1, 1], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [4, 6, 3, 5]);
+    }
+    #[test]
+    fn png_paeth() {
+        let decoded = params(14, 2, 2)
+            .decode(vec![0, 10, 20, 30, 40, 4, 1, 1, 1, 1], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [10, 20, 30, 40, 11, 21, 31, 41]);
+    }
+    #[test]
+    fn tiff_8_bit() {
+        let decoded = params(2, 2, 3)
+            .decode(vec![10, 20, 1, 1, 255, 2, 5, 5, 1, 1, 1, 1], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [10, 20, 11, 21, 10, 23, 5, 5, 6, 6, 7, 7]);
+    }
+    #[test]
+    fn tiff_16_bit() {
+        let mut p = params(2, 1, 2);
+        p.bits_per_component = 16;
+        let decoded = p.decode(vec![0x01, 0xff, 0x00, 0x02], usize::MAX).unwrap();
+        assert_eq!(decoded, [0x01, 0xff, 0x02, 0x01]);
+    }
+    #[test]
+    fn tiff_1_bit() {
+        let mut p = params(2, 1, 10);
+        p.bits_per_component = 1;
+        // Differences 1 0 0 1 0 0 0 0 | 0 1 decode to 1 1 1 0 0 0 0 0 | 0 1.
+        let decoded = p
+            .decode(vec![0b1001_0000, 0b0100_0000], usize::MAX)
+            .unwrap();
+        assert_eq!(decoded, [0b1110_0000, 0b0100_0000]);
+    }
+    #[test]
+    fn tiff_4_bit() {
+        let mut p = params(2, 1, 3);
+        p.bits_per_component = 4;
+        let decoded = p.decode(vec![0x3f, 0x20], usize::MAX).unwrap();
+        assert_eq!(decoded, [0x32, 0x40]);
+    }
+    #[test]
+    fn row_longer_than_data() {
+        assert!(
+            params(12, 1, 1 << 58)
+                .decode(vec![2, 1, 2], usize::MAX)
+                .is_err()
+        );
+        assert!(
+            params(2, 1, 1 << 58)
+                .decode(vec![1, 2], usize::MAX)
+                .is_err()
+        );
+        assert_eq!(
+            params(12, 1, 1 << 58)
+                .decode(Vec::new(), usize::MAX)
+                .unwrap(),
+            []
+        );
+    }
+    #[test]
+    fn predictor_limit() {
+        assert_eq!(
+            params(12, 1, 4)
+                .decode(vec![0, 1, 2, 3, 4, 0, 1], 6)
+                .unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+        assert_eq!(
+            params(2, 1, 8).decode(vec![0; 16], 4).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+    #[test]
+    fn png_invalid_row() {
+        assert!(params(10, 1, 1).decode(vec![5, 1], usize::MAX).is_err());
+    }
+    #[test]
+    fn unsupported_predictor() {
+        assert!(params(7, 1, 1).decode(vec![1], usize::MAX).is_err());
+    }
+    #[test]
+    fn params_from_dictionary() {
+        let (_, dict) = Dictionary::parse(b"<</Predictor 12 /Columns 5 /Colors 3>>").unwrap();
+        let parsed = PredictorParams::new(Some(&dict)).unwrap();
+        assert_eq!(parsed, params(12, 3, 5));
+    }
+}
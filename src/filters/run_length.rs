@@ -0,0 +1,59 @@
+use crate::error::{Error, Result};
+
+/// Decodes run-length data, failing once the output would exceed `limit` bytes.
+pub fn run_length_decode(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut input = data.iter();
+    while let Some(length) = input.next() {
+        match length {
+            0..=127 => {
+                let count = usize::from(*length).saturating_add(1);
+                let literal = input.as_slice().get(..count);
+                let literal = literal.ok_or(Error::Decode("truncated run length data"))?;
+                res.extend_from_slice(literal);
+                input.nth(usize::from(*length));
+            }
+            128 => break,
+            129..=255 => {
+                let byte = input
+                    .next()
+                    .ok_or(Error::Decode("truncated run length data"))?;
+                let count = 257u16.saturating_sub(u16::from(*length));
+                res.extend(core::iter::repeat_n(*byte, usize::from(count)));
+            }
+        }
+        if res.len() > limit {
+            return Err(Error::LimitExceeded("decoded stream size"));
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn run_length_1() {
+        assert_eq!(
+            run_length_decode(&[2, b'a', b'b', b'c', 253, b'x', 128, 0, b'z'], usize::MAX).unwrap(),
+            b"abcxxxx"
+        );
+    }
+    #[test]
+    fn run_length_2() {
+        assert_eq!(run_length_decode(&[129, 7], usize::MAX).unwrap(), [7; 128]);
+    }
+    #[test]
+    fn run_length_3() {
+        assert!(run_length_decode(&[3, b'a'], usize::MAX).is_err());
+        assert!(run_length_decode(&[200], usize::MAX).is_err());
+    }
+    #[test]
+    fn run_length_limit() {
+        assert_eq!(run_length_decode(&[129, 7], 128).unwrap(), [7; 128]);
+        assert_eq!(
+            run_length_decode(&[129, 7], 127).unwrap_err(),
+            Error::LimitExceeded("decoded stream size")
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::{get_numbers, to_array};
+use crate::{error::Error, objects::Array};
+
+/// An affine transformation `[a b c d e f]`, mapping `(x, y)` to
+/// `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Matrix {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+    pub fn translate(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+    pub fn get(&self) -> [f64; 6] {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+    }
+    /// The transformation applying this matrix first and `other` second, which is how `cm`
+    /// combines a matrix with the current transformation matrix.
+    pub fn multiply(&self, other: &Matrix) -> Matrix {
+        Matrix {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+    /// The inverse transformation, or `None` if the matrix is singular.
+    pub fn invert(&self) -> Option<Matrix> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Matrix {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+    pub fn transform_point(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl TryFrom<&Array<'_>> for Matrix {
+    type Error = Error;
+    fn try_from(array: &Array<'_>) -> Result<Self, Error> {
+        let [a, b, c, d, e, f] = get_numbers(array, "matrix")?;
+        Ok(Self::new(a, b, c, d, e, f))
+    }
+}
+
+impl From<Matrix> for Array<'_> {
+    fn from(matrix: Matrix) -> Self {
+        to_array(&matrix.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn matrix_from_array() {
+        let (_, array) = Array::parse(b"[1 0 0 1 72 -36.5]").unwrap();
+        let matrix = Matrix::try_from(&array).unwrap();
+        assert_eq!(matrix, Matrix::translate(72.0, -36.5));
+        let mut out = Vec::new();
+        Array::from(matrix).write_to(&mut out).unwrap();
+        assert_eq!(out, b"[1.0 0.0 0.0 1.0 72.0 -36.5]");
+        let (_, array) = Array::parse(b"[1 0 0 1]").unwrap();
+        assert_eq!(
+            Matrix::try_from(&array).unwrap_err(),
+            Error::UnexpectedLength {
+                expected: "matrix",
+                length: 6,
+                found: 4
+            }
+        );
+        let (_, array) = Array::parse(b"[1 0 0 1 (x) 0]").unwrap();
+        assert_eq!(
+            Matrix::try_from(&array).unwrap_err(),
+            Error::UnexpectedType {
+                expected: "number",
+                found: "String"
+            }
+        );
+    }
+    #[test]
+    fn matrix_multiply() {
+        let m = Matrix::scale(2.0, 3.0).multiply(&Matrix::translate(10.0, 20.0));
+        assert_eq!(m.transform_point(1.0, 1.0), (12.0, 23.0));
+        let m = Matrix::translate(10.0, 20.0).multiply(&Matrix::scale(2.0, 3.0));
+        assert_eq!(m.transform_point(1.0, 1.0), (22.0, 63.0));
+        assert_eq!(Matrix::default().multiply(&m), m);
+    }
+    #[test]
+    fn matrix_invert() {
+        let m = Matrix::new(0.0, 2.0, -1.0, 0.0, 5.0, 7.0);
+        let inverse = m.invert().unwrap();
+        assert_eq!(m.multiply(&inverse), Matrix::identity());
+        let (x, y) = m.transform_point(3.0, 4.0);
+        assert_eq!(inverse.transform_point(x, y), (3.0, 4.0));
+        assert_eq!(Matrix::scale(0.0, 1.0).invert(), None);
+    }
+}
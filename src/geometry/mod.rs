@@ -0,0 +1,37 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod matrix;
+mod rectangle;
+pub use matrix::*;
+pub use rectangle::*;
+
+use crate::{
+    error::{Error, Result},
+    objects::{Array, Object},
+};
+
+/// Reads an array of exactly `N` numbers, each of which may be an integer or a real.
+fn get_numbers<const N: usize>(array: &Array, expected: &'static str) -> Result<[f64; N]> {
+    let mut numbers = [0.0; N];
+    if array.len() != N {
+        return Err(Error::UnexpectedLength {
+            expected,
+            length: N,
+            found: array.len(),
+        });
+    }
+    for (number, object) in numbers.iter_mut().zip(array.get()) {
+        *number = object.as_f64().ok_or(Error::UnexpectedType {
+            expected: "number",
+            found: object.type_name(),
+        })?;
+    }
+    Ok(numbers)
+}
+
+fn to_array<'b>(numbers: &[f64]) -> Array<'b> {
+    numbers.iter().copied().map(Object::from).collect()
+}
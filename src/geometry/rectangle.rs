@@ -0,0 +1,128 @@
+use super::{Matrix, get_numbers, to_array};
+use crate::{error::Error, objects::Array};
+
+/// An axis-aligned rectangle such as a page box, an annotation `/Rect` or a form `/BBox`. The
+/// corners are kept normalized, the lower left one first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    llx: f64,
+    lly: f64,
+    urx: f64,
+    ury: f64,
+}
+
+impl Rectangle {
+    /// Creates the rectangle spanned by two opposite corners, in any order.
+    pub const fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self {
+            llx: x1.min(x2),
+            lly: y1.min(y2),
+            urx: x1.max(x2),
+            ury: y1.max(y2),
+        }
+    }
+    pub fn get_llx(&self) -> f64 {
+        self.llx
+    }
+    pub fn get_lly(&self) -> f64 {
+        self.lly
+    }
+    pub fn get_urx(&self) -> f64 {
+        self.urx
+    }
+    pub fn get_ury(&self) -> f64 {
+        self.ury
+    }
+    pub fn width(&self) -> f64 {
+        self.urx - self.llx
+    }
+    pub fn height(&self) -> f64 {
+        self.ury - self.lly
+    }
+    /// The overlap of both rectangles, or `None` if they do not overlap.
+    pub fn intersect(&self, other: &Rectangle) -> Option<Rectangle> {
+        let rect = Rectangle {
+            llx: self.llx.max(other.llx),
+            lly: self.lly.max(other.lly),
+            urx: self.urx.min(other.urx),
+            ury: self.ury.min(other.ury),
+        };
+        (rect.llx <= rect.urx && rect.lly <= rect.ury).then_some(rect)
+    }
+    /// The smallest rectangle containing this one after transforming it by `matrix`.
+    pub fn transform(&self, matrix: &Matrix) -> Rectangle {
+        let (x1, y1) = matrix.transform_point(self.llx, self.lly);
+        let (x2, y2) = matrix.transform_point(self.llx, self.ury);
+        let (x3, y3) = matrix.transform_point(self.urx, self.lly);
+        let (x4, y4) = matrix.transform_point(self.urx, self.ury);
+        Rectangle {
+            llx: x1.min(x2).min(x3).min(x4),
+            lly: y1.min(y2).min(y3).min(y4),
+            urx: x1.max(x2).max(x3).max(x4),
+            ury: y1.max(y2).max(y3).max(y4),
+        }
+    }
+}
+
+impl TryFrom<&Array<'_>> for Rectangle {
+    type Error = Error;
+    fn try_from(array: &Array<'_>) -> Result<Self, Error> {
+        let [x1, y1, x2, y2] = get_numbers(array, "rectangle")?;
+        Ok(Self::new(x1, y1, x2, y2))
+    }
+}
+
+impl From<Rectangle> for Array<'_> {
+    fn from(rect: Rectangle) -> Self {
+        to_array(&[rect.llx, rect.lly, rect.urx, rect.ury])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn rectangle_from_array() {
+        let (_, array) = Array::parse(b"[612 792.5 0 0]").unwrap();
+        let rect = Rectangle::try_from(&array).unwrap();
+        assert_eq!(rect, Rectangle::new(0.0, 0.0, 612.0, 792.5));
+        assert_eq!(rect.get_urx(), 612.0);
+        assert_eq!(rect.height(), 792.5);
+        let mut out = Vec::new();
+        Array::from(rect).write_to(&mut out).unwrap();
+        assert_eq!(out, b"[0.0 0.0 612.0 792.5]");
+    }
+    #[test]
+    fn rectangle_from_invalid_array() {
+        let (_, array) = Array::parse(b"[0 0 612]").unwrap();
+        assert_eq!(
+            Rectangle::try_from(&array).unwrap_err(),
+            Error::UnexpectedLength {
+                expected: "rectangle",
+                length: 4,
+                found: 3
+            }
+        );
+        let (_, array) = Array::parse(b"[0 0 612 /Tall]").unwrap();
+        assert_eq!(
+            Rectangle::try_from(&array).unwrap_err(),
+            Error::UnexpectedType {
+                expected: "number",
+                found: "Name"
+            }
+        );
+    }
+    #[test]
+    fn rectangle_intersect() {
+        let a = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rectangle::new(5.0, -5.0, 20.0, 5.0);
+        assert_eq!(a.intersect(&b), Some(Rectangle::new(5.0, 0.0, 10.0, 5.0)));
+        assert_eq!(a.intersect(&Rectangle::new(11.0, 0.0, 12.0, 1.0)), None);
+    }
+    #[test]
+    fn rectangle_transform() {
+        let rect = Rectangle::new(0.0, 0.0, 10.0, 20.0);
+        let rotated = rect.transform(&Matrix::new(0.0, 1.0, -1.0, 0.0, 5.0, 0.0));
+        assert_eq!(rotated, Rectangle::new(-15.0, 0.0, 5.0, 10.0));
+    }
+}
@@ -0,0 +1,99 @@
+//! The lexical rules shared by every parser: the PDF whitespace set, `%` comments and
+//! delimiter-terminated tokens.
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::{tag, take_till, take_while_m_n, take_while1},
+    character::complete::{char, digit1},
+    combinator::not,
+    multi::{many0_count, many1_count},
+    sequence::{preceded, terminated},
+};
+
+use crate::error::{ParseError, ParseResult};
+
+pub fn is_whitespace(c: u8) -> bool {
+    matches!(c, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+pub fn is_delimiter(c: u8) -> bool {
+    b"()<>[]{}/%".contains(&c)
+}
+
+/// Whether `c` can be part of a name, number or keyword token.
+pub fn is_regular(c: u8) -> bool {
+    !is_whitespace(c) && !is_delimiter(c)
+}
+
+/// A `%` comment, returning its text without the end-of-line marker.
+pub fn comment(input: &[u8]) -> ParseResult<'_, &[u8]> {
+    preceded(char('%'), take_till(|c| c == b'\r' || c == b'\n')).parse(input)
+}
+
+/// Skips any whitespace and comments.
+pub fn space0(input: &[u8]) -> ParseResult<'_, ()> {
+    many0_count(alt((take_while1(is_whitespace), comment)))
+        .map(|_| ())
+        .parse(input)
+}
+
+/// Skips whitespace and comments, requiring at least one of them.
+pub fn space1(input: &[u8]) -> ParseResult<'_, ()> {
+    many1_count(alt((take_while1(is_whitespace), comment)))
+        .map(|_| ())
+        .parse(input)
+}
+
+/// Matches `word` only when it is not followed by another regular character, so `true` does not
+/// match the start of `trueish`.
+pub fn keyword<'a>(
+    word: &'static str,
+) -> impl Parser<&'a [u8], Output = &'a [u8], Error = ParseError> {
+    terminated(tag(word), not(take_while_m_n(1, 1, is_regular)))
+}
+
+/// An unsigned decimal number without a sign, as used by object numbers and cross-reference
+/// offsets.
+pub(crate) fn parse_unsigned<T: core::str::FromStr>(input: &[u8]) -> ParseResult<'_, T> {
+    digit1
+        .map_res(core::str::from_utf8)
+        .map_res(str::parse)
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn space0_1() {
+        let (rem, ()) = space0(b" \0\t\x0C\r\n% a comment\r\n %another\nx").unwrap();
+        assert_eq!(rem, b"x");
+    }
+    #[test]
+    fn space0_2() {
+        let (rem, ()) = space0(b"x ").unwrap();
+        assert_eq!(rem, b"x ");
+    }
+    #[test]
+    fn space1_1() {
+        assert!(space1(b"x").is_err());
+        let (rem, ()) = space1(b"%c").unwrap();
+        assert!(rem.is_empty());
+    }
+    #[test]
+    fn comment_1() {
+        let (rem, text) = comment(b"%PDF-1.7\r\n").unwrap();
+        assert_eq!(text, b"PDF-1.7");
+        assert_eq!(rem, b"\r\n");
+    }
+    #[test]
+    fn keyword_1() {
+        assert!(keyword("true").parse(b"trueish").is_err());
+        let (rem, _) = keyword("true").parse(b"true]").unwrap();
+        assert_eq!(rem, b"]");
+        let (rem, _) = keyword("true").parse(b"true").unwrap();
+        assert!(rem.is_empty());
+    }
+}
@@ -1 +1,14 @@
+pub mod content;
+pub mod crypt;
+pub mod document;
+pub mod error;
+pub mod filters;
+pub mod geometry;
+pub mod lexer;
+pub mod limits;
 pub mod objects;
+pub mod xref;
+
+pub use document::Document;
+pub use error::{Error, Result};
+pub use limits::Limits;
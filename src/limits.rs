@@ -0,0 +1,85 @@
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+/// Bounds on the resources spent on a single file, to process untrusted input safely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// How deeply arrays and dictionaries may be nested. Parsing recurses once per level, so this
+    /// protects the stack.
+    pub max_depth: usize,
+    /// The largest size a stream may decode to.
+    pub max_decoded_size: usize,
+    /// The largest number of objects a document may have.
+    pub max_objects: usize,
+    /// The largest ratio between the decoded and the encoded size of a stream. Streams decoding
+    /// to at most [`Limits::RATIO_THRESHOLD`] bytes are not checked, as blank images legitimately
+    /// compress far better than any sensible ratio.
+    pub max_ratio: usize,
+    /// The largest number of content stream operations run for a page, those of the form
+    /// XObjects it draws included. A form drawn many times counts every time.
+    pub max_operations: usize,
+}
+
+impl Limits {
+    pub const RATIO_THRESHOLD: usize = 1 << 20;
+
+    /// Limits that never reject a file, except for the nesting depth which protects the stack.
+    pub fn unlimited() -> Self {
+        Self {
+            max_depth: Self::default().max_depth,
+            max_decoded_size: usize::MAX,
+            max_objects: usize::MAX,
+            max_ratio: usize::MAX,
+            max_operations: usize::MAX,
+        }
+    }
+    /// The limits for the content of an array or dictionary, or `None` if it would be nested too
+    /// deeply.
+    pub fn nested(&self) -> Option<Self> {
+        Some(Self {
+            max_depth: self.max_depth.checked_sub(1)?,
+            ..*self
+        })
+    }
+    /// The largest size a stream of `encoded` bytes may decode to.
+    pub fn decoded_size(&self, encoded: usize) -> usize {
+        encoded
+            .saturating_mul(self.max_ratio)
+            .max(Self::RATIO_THRESHOLD)
+            .min(self.max_decoded_size)
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_decoded_size: 256 << 20,
+            max_objects: 8_388_607,
+            max_ratio: 1024,
+            max_operations: 1 << 23,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn decoded_size() {
+        let limits = Limits::default();
+        assert_eq!(limits.decoded_size(10), Limits::RATIO_THRESHOLD);
+        assert_eq!(limits.decoded_size(10 << 10), 10 << 20);
+        assert_eq!(limits.decoded_size(usize::MAX), 256 << 20);
+        assert_eq!(Limits::unlimited().decoded_size(10), usize::MAX);
+    }
+    #[test]
+    fn nested() {
+        let limits = Limits {
+            max_depth: 1,
+            ..Limits::default()
+        };
+        let nested = limits.nested().unwrap();
+        assert_eq!(nested.max_depth, 0);
+        assert!(nested.nested().is_none());
+    }
+}
@@ -1,32 +1,84 @@
-use nom::{
-    IResult, Parser,
-    character::complete::{char, multispace0},
-    multi::many0,
-    sequence::{delimited, preceded},
-};
+use std::io::{self, Write};
 
-use super::Object;
+use nom::{Parser, character::complete::char};
+
+use super::{Object, nested};
+use crate::{error::ParseResult, lexer::space0, limits::Limits};
 #[derive(Debug, Clone, PartialEq)]
 pub struct Array<'b>(Vec<Object<'b>>);
 
 impl<'b> Array<'b> {
-    pub fn parse(input: &'b [u8]) -> IResult<&'b [u8], Array<'b>> {
-        delimited(
-            char('['),
-            many0(preceded(multispace0, Object::parse)),
-            preceded(multispace0, char(']')),
-        )
-        .map(Array)
-        .parse(input)
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Array<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Array<'b>> {
+        let (mut rem, _) = char('[').parse(input)?;
+        let limits = nested(input, limits)?;
+        let mut objects = Vec::new();
+        loop {
+            let (element, ()) = space0(rem)?;
+            let end: ParseResult<'b, char> = char(']').parse(element);
+            if let Ok((rem, _)) = end {
+                return Ok((rem, Array(objects)));
+            }
+            let (next, object) = Object::parse_limited(element, &limits)?;
+            objects.push(object);
+            rem = next;
+        }
+    }
+    pub fn new(objects: Vec<Object<'b>>) -> Self {
+        Self(objects)
     }
     pub fn get(&self) -> &[Object<'b>] {
         &self.0
     }
+    pub fn get_mut(&mut self) -> &mut [Object<'b>] {
+        &mut self.0
+    }
+    pub fn push(&mut self, object: Object<'b>) {
+        self.0.push(object);
+    }
+    pub fn remove(&mut self, index: usize) -> Option<Object<'b>> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"[")?;
+        for (i, object) in self.0.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b" ")?;
+            }
+            object.write_to(w)?;
+        }
+        w.write_all(b"]")
+    }
+    pub fn into_owned(self) -> Array<'static> {
+        Array(self.0.into_iter().map(Object::into_owned).collect())
+    }
+}
+
+impl<'b> FromIterator<Object<'b>> for Array<'b> {
+    fn from_iter<I: IntoIterator<Item = Object<'b>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'b> IntoIterator for Array<'b> {
+    type Item = Object<'b>;
+    type IntoIter = std::vec::IntoIter<Object<'b>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::objects::{Boolean, GetObj, Name, Real};
+    use crate::objects::{Boolean, GetObj, Integer, Name, ObjectId, Real, Reference};
 
     use super::*;
     #[test]
@@ -83,4 +135,57 @@ mod tests {
         let e3: &Boolean = e3.get()[0].get_obj().unwrap();
         assert!(e3.get());
     }
+    #[test]
+    fn parse_array_7() {
+        let (rem, parsed) = Array::parse(b"[1 0 R 2 0 R 3]").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get().len(), 3);
+        let e1: &Reference = parsed.get()[0].get_obj().unwrap();
+        assert_eq!(e1.get(), ObjectId::new(1, 0));
+        let e2: &Reference = parsed.get()[1].get_obj().unwrap();
+        assert_eq!(e2.get(), ObjectId::new(2, 0));
+        let e3: &Integer = parsed.get()[2].get_obj().unwrap();
+        assert_eq!(e3.get(), 3);
+    }
+    #[test]
+    fn parse_array_8() {
+        let (rem, parsed) = Array::parse(b"[0 0 612 792]").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get().len(), 4);
+        let e4: &Integer = parsed.get()[3].get_obj().unwrap();
+        assert_eq!(e4.get(), 792);
+    }
+    #[test]
+    fn write_array_1() {
+        let input = b"[1 2.5 /Name (text) [true null] 3 0 R]";
+        let (_, parsed) = Array::parse(input).unwrap();
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+    #[test]
+    fn write_array_2() {
+        let mut out = Vec::new();
+        Array(vec![]).write_to(&mut out).unwrap();
+        assert_eq!(out, b"[]");
+    }
+    #[test]
+    fn edit_array_1() {
+        let (_, mut parsed) = Array::parse(b"[1 2 3]").unwrap();
+        parsed.push(Object::Null(crate::objects::Null));
+        assert_eq!(parsed.remove(0), Some(Object::Integer(Integer::new(1))));
+        assert_eq!(parsed.remove(3), None);
+        assert_eq!(parsed.len(), 3);
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, b"[2 3 null]");
+    }
+    #[test]
+    fn parse_array_comment_1() {
+        let (rem, parsed) = Array::parse(b"[1 %note\n 2\x0C\x003%]\n]").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.len(), 3);
+        let i: &Integer = parsed.get()[2].get_obj().unwrap();
+        assert_eq!(i.get(), 3);
+    }
 }
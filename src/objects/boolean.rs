@@ -1,12 +1,16 @@
-use nom::{IResult, Parser, branch::alt, bytes::complete::tag};
+use std::io::{self, Write};
+
+use nom::{Parser, branch::alt};
+
+use crate::{error::ParseResult, lexer::keyword};
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Boolean(bool);
 
 impl Boolean {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], Boolean> {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Boolean> {
         alt((
-            tag("true").map(|_| Boolean(true)),
-            tag("false").map(|_| Boolean(false)),
+            keyword("true").map(|_| Boolean(true)),
+            keyword("false").map(|_| Boolean(false)),
         ))
         .parse(input)
     }
@@ -16,6 +20,9 @@ impl Boolean {
     pub fn new(b: bool) -> Self {
         Self(b)
     }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(if self.0 { b"true" } else { b"false" })
+    }
 }
 
 #[cfg(test)]
@@ -35,8 +42,9 @@ mod tests {
     }
     #[test]
     fn boolean_3() {
-        let (rem, parsed) = Boolean::parse(b"truebu").unwrap();
-        assert_eq!(rem, b"bu");
+        assert!(Boolean::parse(b"truebu").is_err());
+        let (rem, parsed) = Boolean::parse(b"true/bu").unwrap();
+        assert_eq!(rem, b"/bu");
         assert_eq!(parsed, Boolean(true));
     }
     #[test]
@@ -44,4 +52,11 @@ mod tests {
         let res = Boolean::parse(b" true");
         assert!(res.is_err())
     }
+    #[test]
+    fn write_boolean_1() {
+        let mut out = Vec::new();
+        Boolean(true).write_to(&mut out).unwrap();
+        Boolean(false).write_to(&mut out).unwrap();
+        assert_eq!(out, b"truefalse");
+    }
 }
@@ -0,0 +1,394 @@
+use std::fmt;
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::char,
+    combinator::{opt, verify},
+    error::context,
+};
+
+use super::String;
+use crate::error::{Error, ParseResult};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Days from 0000-03-01 to 1970-01-01 in the proleptic Gregorian calendar.
+const UNIX_EPOCH_DAYS: i64 = 719_468;
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// A date as found in `/CreationDate`, `/ModDate` or `/M`, written `D:YYYYMMDDHHmmSSOHH'mm'`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PdfDate {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    offset: Option<i16>,
+}
+
+impl PdfDate {
+    /// Parses a date, tolerating a missing `D:` prefix, truncated fields, missing apostrophes in
+    /// the offset and a `Z` followed by an offset.
+    pub fn parse(input: &[u8]) -> ParseResult<'_, PdfDate> {
+        context(
+            "invalid date",
+            verify(Self::parse_fields, |date: &PdfDate| date.is_valid()),
+        )
+        .parse(input)
+    }
+    fn parse_fields(input: &[u8]) -> ParseResult<'_, PdfDate> {
+        let (mut rem, (_, year)) = (opt(tag("D:")), digits::<u16>(4)).parse(input)?;
+        // Month and day default to 1, the time to midnight.
+        let mut fields = [1, 1, 0, 0, 0];
+        for field in &mut fields {
+            let Ok((next, value)) = digits::<u8>(2).parse(rem) else {
+                break;
+            };
+            *field = value;
+            rem = next;
+        }
+        let [month, day, hour, minute, second] = fields;
+        let (rem, offset) = opt(parse_offset).parse(rem)?;
+        Ok((
+            rem,
+            PdfDate {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                offset: offset.flatten(),
+            },
+        ))
+    }
+    /// Creates a date at midnight of an unknown time zone, or `None` if the day does not exist.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let date = Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            offset: None,
+        };
+        date.is_valid().then_some(date)
+    }
+    /// Sets the time of day, or returns `None` if it is out of range.
+    pub fn with_time(self, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        let date = Self {
+            hour,
+            minute,
+            second,
+            ..self
+        };
+        date.is_valid().then_some(date)
+    }
+    /// Sets the offset from UTC in minutes, or returns `None` if it is a day or more.
+    pub fn with_offset(self, offset: i16) -> Option<Self> {
+        let date = Self {
+            offset: Some(offset),
+            ..self
+        };
+        date.is_valid().then_some(date)
+    }
+    /// Converts a Unix timestamp to a date at the given offset from UTC in minutes.
+    pub fn from_timestamp(timestamp: i64, offset: i16) -> Option<Self> {
+        let local = timestamp.checked_add(i64::from(offset).checked_mul(60)?)?;
+        let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY))?;
+        let seconds = local.rem_euclid(SECONDS_PER_DAY);
+        let hour = seconds.checked_div(3600)?;
+        let minute = seconds.checked_rem(3600)?.checked_div(60)?;
+        let second = seconds.checked_rem(60)?;
+        Self::new(year.try_into().ok()?, month, day)?
+            .with_time(
+                hour.try_into().ok()?,
+                minute.try_into().ok()?,
+                second.try_into().ok()?,
+            )?
+            .with_offset(offset)
+    }
+    /// Converts the date to a Unix timestamp. A date without an offset is taken to be in UTC.
+    pub fn to_timestamp(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        let seconds = i64::from(self.hour)
+            .saturating_mul(3600)
+            .saturating_add(i64::from(self.minute).saturating_mul(60))
+            .saturating_add(i64::from(self.second));
+        let offset = i64::from(self.offset.unwrap_or(0)).saturating_mul(60);
+        days.saturating_mul(SECONDS_PER_DAY)
+            .saturating_add(seconds)
+            .saturating_sub(offset)
+    }
+    pub fn get_year(&self) -> u16 {
+        self.year
+    }
+    pub fn get_month(&self) -> u8 {
+        self.month
+    }
+    pub fn get_day(&self) -> u8 {
+        self.day
+    }
+    pub fn get_hour(&self) -> u8 {
+        self.hour
+    }
+    pub fn get_minute(&self) -> u8 {
+        self.minute
+    }
+    pub fn get_second(&self) -> u8 {
+        self.second
+    }
+    /// The offset from UTC in minutes, or `None` if the time zone is unknown.
+    pub fn get_offset(&self) -> Option<i16> {
+        self.offset
+    }
+    fn is_valid(&self) -> bool {
+        self.year <= 9999
+            && (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+            && self.offset.is_none_or(|o| o.unsigned_abs() < 24 * 60)
+    }
+}
+
+/// Writes the canonical form, `D:YYYYMMDDHHmmSS` followed by `Z` or `OHH'mm'` if the offset is
+/// known.
+impl fmt::Display for PdfDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "D:{:04}{:02}{:02}{:02}{:02}{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        match self.offset {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                let (hours, minutes) = (offset.div_euclid(60), offset.rem_euclid(60));
+                write!(f, "{sign}{hours:02}'{minutes:02}'")
+            }
+        }
+    }
+}
+
+impl TryFrom<&String> for PdfDate {
+    type Error = Error;
+    /// Parses a date from a text string, which some writers encode as UTF-16.
+    fn try_from(s: &String) -> Result<Self, Error> {
+        let text = s.to_text();
+        let (_, date) = Self::parse(text.trim_start().as_bytes())?;
+        Ok(date)
+    }
+}
+
+impl From<PdfDate> for String {
+    fn from(date: PdfDate) -> Self {
+        String::new(date.to_string().into_bytes())
+    }
+}
+
+fn digits<T: core::str::FromStr>(n: usize) -> impl Fn(&[u8]) -> ParseResult<'_, T> {
+    move |input| {
+        take_while_m_n(n, n, |c: u8| c.is_ascii_digit())
+            .map_res(core::str::from_utf8)
+            .map_res(str::parse)
+            .parse(input)
+    }
+}
+
+/// Parses `Z`, `+HH'mm'` or `-HH'mm'` with any of the parts after the sign missing. `Z` is
+/// sometimes followed by a zero offset, which is skipped.
+fn parse_offset(input: &[u8]) -> ParseResult<'_, Option<i16>> {
+    let rest = || {
+        (
+            opt(digits::<i16>(2)),
+            opt(char('\'')),
+            opt(digits::<i16>(2)),
+            opt(char('\'')),
+        )
+    };
+    alt((
+        (char('Z'), rest()).map(|_| Some(0)),
+        (alt((char('+'), char('-'))), rest()).map(|(sign, (hours, _, minutes, _))| {
+            let offset = hours
+                .unwrap_or(0)
+                .checked_mul(60)?
+                .checked_add(minutes.unwrap_or(0))?;
+            if sign == '-' {
+                offset.checked_neg()
+            } else {
+                Some(offset)
+            }
+        }),
+    ))
+    .parse(input)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days since 1970-01-01, after Howard Hinnant's `days_from_civil`. The year is at
+/// most 9999, so no step can overflow.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    // Counting from March puts the leap day at the end of the year.
+    let year = if month <= 2 {
+        year.saturating_sub(1)
+    } else {
+        year
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let shifted_month = if month > 2 {
+        month.saturating_sub(3)
+    } else {
+        month.saturating_add(9)
+    };
+    let day_of_year = shifted_month
+        .saturating_mul(153)
+        .saturating_add(2)
+        .div_euclid(5)
+        .saturating_add(i64::from(day))
+        .saturating_sub(1);
+    let day_of_era = year_of_era
+        .saturating_mul(365)
+        .saturating_add(year_of_era.div_euclid(4))
+        .saturating_sub(year_of_era.div_euclid(100))
+        .saturating_add(day_of_year);
+    era.saturating_mul(DAYS_PER_ERA)
+        .saturating_add(day_of_era)
+        .saturating_sub(UNIX_EPOCH_DAYS)
+}
+
+/// The inverse of [`days_from_civil`], after Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> Option<(i64, u8, u8)> {
+    let days = days.checked_add(UNIX_EPOCH_DAYS)?;
+    let era = days.div_euclid(DAYS_PER_ERA);
+    let day_of_era = days.rem_euclid(DAYS_PER_ERA);
+    let year_of_era = day_of_era
+        .checked_sub(day_of_era.div_euclid(1460))?
+        .checked_add(day_of_era.div_euclid(36_524))?
+        .checked_sub(day_of_era.div_euclid(DAYS_PER_ERA.checked_sub(1)?))?
+        .div_euclid(365);
+    let day_of_year = day_of_era.checked_sub(
+        year_of_era
+            .checked_mul(365)?
+            .checked_add(year_of_era.div_euclid(4))?
+            .checked_sub(year_of_era.div_euclid(100))?,
+    )?;
+    let shifted_month = day_of_year.checked_mul(5)?.checked_add(2)?.div_euclid(153);
+    let day = day_of_year
+        .checked_sub(
+            shifted_month
+                .checked_mul(153)?
+                .checked_add(2)?
+                .div_euclid(5),
+        )?
+        .checked_add(1)?;
+    let month = if shifted_month < 10 {
+        shifted_month.checked_add(3)?
+    } else {
+        shifted_month.checked_sub(9)?
+    };
+    let year = era
+        .checked_mul(400)?
+        .checked_add(year_of_era)?
+        .checked_add(i64::from(month <= 2))?;
+    Some((year, month.try_into().ok()?, day.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn parse_date_1() {
+        let (rem, date) = PdfDate::parse(b"D:20230115103000+01'00'").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            date,
+            PdfDate::new(2023, 1, 15)
+                .unwrap()
+                .with_time(10, 30, 0)
+                .unwrap()
+                .with_offset(60)
+                .unwrap()
+        );
+    }
+    #[test]
+    fn parse_date_2() {
+        let (_, date) = PdfDate::parse(b"D:19991231235959-0830").unwrap();
+        assert_eq!(date.get_offset(), Some(-510));
+        let (_, date) = PdfDate::parse(b"D:20230115103000Z00'00'").unwrap();
+        assert_eq!(date.get_offset(), Some(0));
+        let (_, date) = PdfDate::parse(b"D:20230115103000+05").unwrap();
+        assert_eq!(date.get_offset(), Some(300));
+    }
+    #[test]
+    fn parse_date_3() {
+        let (_, date) = PdfDate::parse(b"D:2023").unwrap();
+        assert_eq!(date, PdfDate::new(2023, 1, 1).unwrap());
+        let (_, date) = PdfDate::parse(b"2023021314").unwrap();
+        assert_eq!(
+            date,
+            PdfDate::new(2023, 2, 13)
+                .unwrap()
+                .with_time(14, 0, 0)
+                .unwrap()
+        );
+    }
+    #[test]
+    fn parse_date_error() {
+        assert!(PdfDate::parse(b"D:20230230").is_err());
+        assert!(PdfDate::parse(b"D:20231301").is_err());
+        assert!(PdfDate::parse(b"D:202").is_err());
+        assert!(PdfDate::parse(b"D:20230101+25'00'").is_err());
+        let (_, date) = PdfDate::parse(b"D:20240229").unwrap();
+        assert_eq!(date.get_day(), 29);
+    }
+    #[test]
+    fn date_from_string() {
+        let s = String::from_text("D:20230115103000Z");
+        let date = PdfDate::try_from(&s).unwrap();
+        assert_eq!(date.get_hour(), 10);
+        let s = String::new(b"\xfe\xff\x00D\x00:\x002\x000\x002\x003".to_vec());
+        assert_eq!(PdfDate::try_from(&s).unwrap().get_year(), 2023);
+        assert!(PdfDate::try_from(&String::from_text("yesterday")).is_err());
+    }
+    #[test]
+    fn write_date() {
+        let (_, date) = PdfDate::parse(b"D:20230115103000-0530").unwrap();
+        assert_eq!(date.to_string(), "D:20230115103000-05'30'");
+        let (_, date) = PdfDate::parse(b"D:2023").unwrap();
+        assert_eq!(String::from(date).get(), b"D:20230101000000");
+        let (_, date) = PdfDate::parse(b"D:20230115103000Z").unwrap();
+        assert_eq!(date.to_string(), "D:20230115103000Z");
+    }
+    #[test]
+    fn date_timestamp() {
+        let (_, date) = PdfDate::parse(b"D:20230115103000+01'00'").unwrap();
+        assert_eq!(date.to_timestamp(), 1_673_775_000);
+        assert_eq!(PdfDate::from_timestamp(1_673_775_000, 60), Some(date));
+        let (_, epoch) = PdfDate::parse(b"D:19700101").unwrap();
+        assert_eq!(epoch.to_timestamp(), 0);
+        let (_, leap) = PdfDate::parse(b"D:20000229235959Z").unwrap();
+        assert_eq!(PdfDate::from_timestamp(leap.to_timestamp(), 0), Some(leap));
+        let early = PdfDate::from_timestamp(-1, -60).unwrap();
+        assert_eq!(early.to_string(), "D:19691231225959-01'00'");
+        assert_eq!(PdfDate::from_timestamp(i64::MAX, 0), None);
+    }
+}
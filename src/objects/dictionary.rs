@@ -1,47 +1,135 @@
-use std::collections::HashMap;
+use std::io::{self, Write};
 
-use nom::{
-    IResult, Parser,
-    bytes::complete::tag,
-    character::complete::multispace0,
-    multi::many0,
-    sequence::{delimited, pair, preceded},
-};
+use indexmap::IndexMap;
+use nom::{Parser, bytes::complete::tag, error::context, sequence::preceded};
 
-use super::{Name, Object};
+use super::{Name, Object, nested};
+use crate::{
+    error::{ParseError, ParseResult},
+    lexer::space0,
+    limits::Limits,
+};
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Dictionary<'b>(HashMap<Name<'b>, Object<'b>>);
+/// A dictionary that keeps its entries in the order they were parsed or inserted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dictionary<'b>(IndexMap<Name<'b>, Object<'b>>);
 
 impl<'b> Dictionary<'b> {
-    pub fn get(&self, key: &Name<'b>) -> Option<&Object> {
-        self.0.get(key)
-    }
-    pub fn parse(input: &'b [u8]) -> IResult<&'b [u8], Dictionary<'b>> {
-        delimited(
-            tag("<<"),
-            many0(pair(
-                preceded(multispace0, Name::parse),
-                preceded(multispace0, Object::parse),
-            )),
-            preceded(multispace0, tag(">>")),
-        )
-        .map(|v| {
-            v.into_iter()
-                .filter(|(_k, v)| *v != Object::Null(super::Null))
-                .collect::<HashMap<Name, Object>>()
-        })
-        .map(Dictionary)
-        .parse(input)
+    pub fn new() -> Self {
+        Self(IndexMap::new())
+    }
+    /// Inserts an entry, keeping the position of the key if it was already present.
+    pub fn insert(&mut self, key: Name<'b>, value: Object<'b>) -> Option<Object<'b>> {
+        self.0.insert(key, value)
+    }
+    pub fn get(&self, key: &Name<'_>) -> Option<&Object<'b>> {
+        self.0.get(key.get())
+    }
+    pub fn get_mut(&mut self, key: &Name<'_>) -> Option<&mut Object<'b>> {
+        self.0.get_mut(key.get())
+    }
+    /// Removes an entry, preserving the order of the remaining ones.
+    pub fn remove(&mut self, key: &Name<'_>) -> Option<Object<'b>> {
+        self.0.shift_remove(key.get())
+    }
+    pub fn contains_key(&self, key: &Name<'_>) -> bool {
+        self.0.contains_key(key.get())
+    }
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Dictionary<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Dictionary<'b>> {
+        let (mut rem, _) = tag("<<").parse(input)?;
+        let limits = nested(input, limits)?;
+        let mut dictionary = Dictionary::new();
+        loop {
+            let (entry, ()) = space0(rem)?;
+            let end: ParseResult<'b, &[u8]> = tag(">>").parse(entry);
+            if let Ok((rem, _)) = end {
+                return Ok((rem, dictionary));
+            }
+            let (value, key) = context("expected a name or the end of the dictionary", Name::parse)
+                .parse(entry)?;
+            let (next, value) = preceded(space0, |i| Object::parse_limited(i, &limits))
+                .parse(value)
+                .map_err(|err| err.map(|e: ParseError| e.key(&key)))?;
+            if value != Object::Null(super::Null) {
+                dictionary.insert(key, value);
+            }
+            rem = next;
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&Name<'b>, &Object<'b>)> {
+        self.0.iter()
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Name<'b>, &mut Object<'b>)> {
+        self.0.iter_mut()
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &Name<'b>> {
+        self.0.keys()
+    }
+    pub fn values(&self) -> impl Iterator<Item = &Object<'b>> {
+        self.0.values()
+    }
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Object<'b>> {
+        self.0.values_mut()
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"<<")?;
+        for (i, (key, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b" ")?;
+            }
+            key.write_to(w)?;
+            w.write_all(b" ")?;
+            value.write_to(w)?;
+        }
+        w.write_all(b">>")
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
     }
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    pub fn into_owned(self) -> Dictionary<'static> {
+        self.into_iter()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect()
+    }
+}
+
+impl<'b> FromIterator<(Name<'b>, Object<'b>)> for Dictionary<'b> {
+    fn from_iter<I: IntoIterator<Item = (Name<'b>, Object<'b>)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'b> Extend<(Name<'b>, Object<'b>)> for Dictionary<'b> {
+    fn extend<I: IntoIterator<Item = (Name<'b>, Object<'b>)>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<'b> IntoIterator for Dictionary<'b> {
+    type Item = (Name<'b>, Object<'b>);
+    type IntoIter = indexmap::map::IntoIter<Name<'b>, Object<'b>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'a Dictionary<'b> {
+    type Item = (&'a Name<'b>, &'a Object<'b>);
+    type IntoIter = indexmap::map::Iter<'a, Name<'b>, Object<'b>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::objects::{GetObj, Real};
+    use crate::objects::{GetObj, Integer, ObjectId, Real, Reference};
 
     use super::*;
     #[test]
@@ -124,4 +212,144 @@ mod tests {
         let sub = parsed.get(&Name::new(b"Sub"));
         assert!(sub.is_none())
     }
+    #[test]
+    fn parse_dictionary_8() {
+        let (rem, parsed) = Dictionary::parse(b"<</Type /Page /Parent 4 0 R /Rotate 90>>").unwrap();
+        assert!(rem.is_empty());
+        let p: &Reference = parsed
+            .get(&Name::new(b"Parent"))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(p.get(), ObjectId::new(4, 0));
+        let r: &Integer = parsed
+            .get(&Name::new(b"Rotate"))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(r.get(), 90);
+    }
+    #[test]
+    fn write_dictionary_1() {
+        let mut out = Vec::new();
+        Dictionary::parse(b"<</Type /Page>>")
+            .unwrap()
+            .1
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, b"<</Type /Page>>");
+    }
+    #[test]
+    fn write_dictionary_2() {
+        let input = b"<</Kids [4 0 R 5 0 R] /Count 2 /Parent <</Size 1.5 /Name (x)>>>>";
+        let (_, parsed) = Dictionary::parse(input).unwrap();
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+    #[test]
+    fn dictionary_order_1() {
+        let (_, parsed) = Dictionary::parse(b"<</Type /Page /Parent 4 0 R /Rotate 90>>").unwrap();
+        let keys: Vec<&[u8]> = parsed.keys().map(Name::get).collect();
+        assert_eq!(keys, [b"Type".as_slice(), b"Parent", b"Rotate"]);
+    }
+    #[test]
+    fn dictionary_edit_1() {
+        let (_, mut parsed) =
+            Dictionary::parse(b"<</Type /Page /Parent 4 0 R /Rotate 90>>").unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert!(parsed.contains_key(&Name::new(b"Parent")));
+        let old = parsed.insert(Name::new(b"Type"), Object::Name(Name::new(b"Template")));
+        assert_eq!(old, Some(Object::Name(Name::new(b"Page"))));
+        parsed.insert(Name::new(b"Contents"), Object::Integer(Integer::new(5)));
+        let removed = parsed.remove(&Name::new(b"Parent"));
+        assert_eq!(
+            removed,
+            Some(Object::Reference(Reference::new(ObjectId::new(4, 0))))
+        );
+        assert_eq!(parsed.remove(&Name::new(b"Parent")), None);
+        if let Some(Object::Integer(rotate)) = parsed.get_mut(&Name::new(b"Rotate")) {
+            *rotate = Integer::new(180);
+        }
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, b"<</Type /Template /Rotate 180 /Contents 5>>");
+    }
+    #[test]
+    fn dictionary_iter_1() {
+        let mut dictionary: Dictionary = [
+            (Name::new(b"A"), Object::Integer(Integer::new(1))),
+            (Name::new(b"B"), Object::Integer(Integer::new(2))),
+        ]
+        .into_iter()
+        .collect();
+        for (_, value) in dictionary.iter_mut() {
+            *value = Object::Boolean(crate::objects::Boolean::new(true));
+        }
+        let entries: Vec<(&Name, &Object)> = (&dictionary).into_iter().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].0.get(), b"B");
+        assert!(
+            dictionary
+                .values()
+                .all(|v| *v == Object::Boolean(crate::objects::Boolean::new(true)))
+        );
+        let owned: Vec<(Name, Object)> = dictionary.into_iter().collect();
+        assert_eq!(owned[0].0, Name::new(b"A"));
+    }
+    #[test]
+    fn parse_dictionary_comment_1() {
+        let (rem, parsed) =
+            Dictionary::parse(b"<< /Type % the type\r\n /Page\x00/Rotate%\n90 % end >>\n>>")
+                .unwrap();
+        assert!(rem.is_empty());
+        let t: &Name = parsed.get(&Name::new(b"Type")).unwrap().get_obj().unwrap();
+        assert_eq!(t.get(), b"Page");
+        let r: &Integer = parsed
+            .get(&Name::new(b"Rotate"))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(r.get(), 90);
+    }
+    #[test]
+    fn parse_dictionary_delimiters_1() {
+        let (rem, parsed) = Dictionary::parse(b"<</Type/Page/Kids[1 0 R]/Open true>>").unwrap();
+        assert!(rem.is_empty());
+        let t: &Name = parsed.get(&Name::new(b"Type")).unwrap().get_obj().unwrap();
+        assert_eq!(t.get(), b"Page");
+        assert!(parsed.contains_key(&Name::new(b"Kids")));
+        assert!(parsed.contains_key(&Name::new(b"Open")));
+    }
+    #[test]
+    fn parse_dictionary_error_1() {
+        let input = b"<</A 1 /B <</C [1 2] /D (x>>>>";
+        let err = match Dictionary::parse(input) {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "unterminated literal string");
+        assert_eq!(err.get_path(), [Name::new(b"B"), Name::new(b"D")]);
+        let err = err.locate(input);
+        assert_eq!(err.get_offset(), Some(25));
+        assert_eq!(
+            err.to_string(),
+            "unterminated literal string at /B/D (offset 25)"
+        );
+    }
+    #[test]
+    fn parse_dictionary_error_2() {
+        let input = b"<</A 1 2>>";
+        let err = match Dictionary::parse(input) {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(
+            err.get_reason(),
+            "expected a name or the end of the dictionary"
+        );
+        assert_eq!(err.locate(input).get_offset(), Some(7));
+    }
 }
@@ -0,0 +1,167 @@
+use std::io::{self, Write};
+
+use nom::{
+    Parser,
+    error::context,
+    sequence::{delimited, preceded, terminated},
+};
+
+use super::{Dictionary, Name, Object, ObjectId, Stream};
+use crate::{
+    error::ParseResult,
+    lexer::{keyword, space0, space1},
+    limits::Limits,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndirectObject<'b> {
+    id: ObjectId,
+    object: Object<'b>,
+}
+
+impl<'b> IndirectObject<'b> {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, IndirectObject<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, IndirectObject<'b>> {
+        Self::parse_with_length(input, limits, |_| None)
+    }
+    /// Parses an indirect object, calling `length` to resolve a `/Length` of a stream that is an
+    /// indirect reference.
+    pub fn parse_with_length(
+        input: &'b [u8],
+        limits: &Limits,
+        length: impl Fn(ObjectId) -> Option<usize>,
+    ) -> ParseResult<'b, IndirectObject<'b>> {
+        let (rem, id) = Self::parse_header(input)?;
+        delimited(
+            space0,
+            |i| Self::parse_body(i, limits, &length),
+            (space0, context("missing endobj", keyword("endobj"))),
+        )
+        .map(|object| IndirectObject { id, object })
+        .parse(rem)
+        .map_err(|err| err.map(|e| e.object(id)))
+    }
+    /// Streams are always indirect objects, so only the top level needs to resolve lengths.
+    fn parse_body(
+        input: &'b [u8],
+        limits: &Limits,
+        length: &impl Fn(ObjectId) -> Option<usize>,
+    ) -> ParseResult<'b, Object<'b>> {
+        let (rem, info) = match Dictionary::parse_limited(input, limits) {
+            Ok(res) => res,
+            Err(nom::Err::Error(_)) => return Object::parse_limited(input, limits),
+            Err(err) => return Err(err),
+        };
+        let Ok((data, ())) = Stream::parse_keyword(rem) else {
+            return Ok((rem, Object::Dictionary(info)));
+        };
+        let length = match info.get(&Name::new(b"Length")) {
+            Some(Object::Reference(r)) => length(r.get()),
+            _ => None,
+        };
+        Stream::parse_data_with_length(info, data, length).map(|(rem, s)| (rem, Object::Stream(s)))
+    }
+    pub fn parse_header(input: &[u8]) -> ParseResult<'_, ObjectId> {
+        context(
+            "expected an object header",
+            terminated(ObjectId::parse, preceded(space1, keyword("obj"))),
+        )
+        .parse(input)
+    }
+    pub fn new(id: ObjectId, object: Object<'b>) -> Self {
+        Self { id, object }
+    }
+    pub fn get_id(&self) -> ObjectId {
+        self.id
+    }
+    pub fn get_object(&self) -> &Object<'b> {
+        &self.object
+    }
+    pub fn into_object(self) -> Object<'b> {
+        self.object
+    }
+    pub fn into_owned(self) -> IndirectObject<'static> {
+        IndirectObject {
+            id: self.id,
+            object: self.object.into_owned(),
+        }
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        self.id.write_to(w)?;
+        w.write_all(b" obj\n")?;
+        self.object.write_to(w)?;
+        w.write_all(b"\nendobj\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::{Dictionary, GetObj, Integer, Name, Reference};
+
+    use super::*;
+    #[test]
+    fn parse_indirect_1() {
+        let (rem, parsed) = IndirectObject::parse(b"12 0 obj\n(Brilling)\nendobj").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_id(), ObjectId::new(12, 0));
+        let s: &crate::objects::String = parsed.get_object().get_obj().unwrap();
+        assert_eq!(s.get(), b"Brilling");
+    }
+    #[test]
+    fn parse_indirect_2() {
+        let (rem, parsed) =
+            IndirectObject::parse(b"1 0 obj<</Type /Catalog /Pages 2 0 R>>endobj\n").unwrap();
+        assert_eq!(rem, b"\n");
+        let dict: &Dictionary = parsed.get_object().get_obj().unwrap();
+        let pages: &Reference = dict.get(&Name::new(b"Pages")).unwrap().get_obj().unwrap();
+        assert_eq!(pages.get(), ObjectId::new(2, 0));
+    }
+    #[test]
+    fn parse_indirect_3() {
+        let (rem, parsed) = IndirectObject::parse(b"8 1 obj 42 endobj").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_id(), ObjectId::new(8, 1));
+        let i: &Integer = parsed.get_object().get_obj().unwrap();
+        assert_eq!(i.get(), 42);
+    }
+    #[test]
+    fn parse_indirect_4() {
+        let parsed = IndirectObject::parse(b"8 1 obj 42");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn parse_indirect_header_1() {
+        let (rem, parsed) = IndirectObject::parse_header(b"3 0 obj <<>>").unwrap();
+        assert_eq!(rem, b" <<>>");
+        assert_eq!(parsed, ObjectId::new(3, 0));
+    }
+    #[test]
+    fn write_indirect_1() {
+        let input = b"12 0 obj\n(Brilling)\nendobj\n";
+        let (_, parsed) = IndirectObject::parse(input).unwrap();
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+    #[test]
+    fn parse_indirect_comment_1() {
+        let (rem, parsed) =
+            IndirectObject::parse(b"12 0 obj % comment\n(Brilling) %another\nendobj").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_id(), ObjectId::new(12, 0));
+    }
+    #[test]
+    fn parse_indirect_length_1() {
+        let input = b"4 0 obj <</Length 5 0 R>>stream\na endstream b\nendstream\nendobj";
+        let (rem, parsed) = IndirectObject::parse_with_length(input, &Limits::default(), |id| {
+            (id == ObjectId::new(5, 0)).then_some(13)
+        })
+        .unwrap();
+        assert!(rem.is_empty());
+        let stream: &Stream = parsed.get_object().get_obj().unwrap();
+        assert_eq!(stream.get_data(), b"a endstream b");
+        assert!(IndirectObject::parse(input).is_err());
+    }
+}
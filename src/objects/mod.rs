@@ -5,21 +5,36 @@
 
 mod array;
 mod boolean;
+mod date;
 mod dictionary;
+mod indirect;
 mod name;
 mod null;
 mod number;
+mod reference;
 mod stream;
 mod string;
+mod text;
+use std::io::{self, Write};
+
 pub use array::*;
 pub use boolean::*;
+pub use date::*;
 pub use dictionary::*;
+pub use indirect::*;
 pub use name::*;
-use nom::{IResult, Parser, branch::alt};
+use nom::{Parser, branch::alt, error::context};
+
+use crate::{
+    error::{Error, ParseError, ParseResult},
+    limits::Limits,
+};
 pub use null::*;
 pub use number::*;
+pub use reference::*;
 pub use stream::*;
 pub use string::*;
+pub use text::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Object<'b> {
@@ -32,27 +47,204 @@ pub enum Object<'b> {
     Null(Null),
     Dictionary(Dictionary<'b>),
     Stream(Stream<'b>),
+    Reference(Reference),
 }
 
 impl<'b> Object<'b> {
-    pub fn parse(input: &'b [u8]) -> IResult<&'b [u8], Object<'b>> {
-        alt((
-            Name::parse.map(Object::Name),
-            Integer::parse.map(Object::Integer),
-            Stream::parse.map(Object::Stream),
-            Dictionary::parse.map(Object::Dictionary),
-            String::parse.map(Object::String),
-            Real::parse.map(Object::Real),
-            Boolean::parse.map(Object::Boolean),
-            Array::parse.map(Object::Array),
-            Null::parse.map(Object::Null),
-        ))
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Object<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    /// Parses an object, failing if arrays and dictionaries are nested deeper than allowed.
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Object<'b>> {
+        // An integer literal too large for `Integer` is read as a `Real` by the later branch.
+        context(
+            "expected an object",
+            alt((
+                Name::parse.map(Object::Name),
+                Reference::parse.map(Object::Reference),
+                Integer::parse.map(Object::Integer),
+                |i| Self::parse_dictionary_or_stream(i, limits),
+                String::parse.map(Object::String),
+                Real::parse.map(Object::Real),
+                Boolean::parse.map(Object::Boolean),
+                (|i| Array::parse_limited(i, limits)).map(Object::Array),
+                Null::parse.map(Object::Null),
+            )),
+        )
         .parse(input)
     }
+    /// Parses a dictionary once and only then looks for the `stream` keyword, so that nested
+    /// dictionaries are not parsed again for every level.
+    fn parse_dictionary_or_stream(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Object<'b>> {
+        let (rem, info) = Dictionary::parse_limited(input, limits)?;
+        match Stream::parse_keyword(rem) {
+            Ok((data, ())) => {
+                Stream::parse_data(info, data).map(|(rem, s)| (rem, Object::Stream(s)))
+            }
+            Err(_) => Ok((rem, Object::Dictionary(info))),
+        }
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        match self {
+            Object::Boolean(b) => b.write_to(w),
+            Object::Name(n) => n.write_to(w),
+            Object::Integer(i) => i.write_to(w),
+            Object::Real(r) => r.write_to(w),
+            Object::String(s) => s.write_to(w),
+            Object::Array(a) => a.write_to(w),
+            Object::Null(n) => n.write_to(w),
+            Object::Dictionary(d) => d.write_to(w),
+            Object::Stream(s) => s.write_to(w),
+            Object::Reference(r) => r.write_to(w),
+        }
+    }
+    /// The name of the type of the object, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Boolean(_) => "Boolean",
+            Object::Name(_) => "Name",
+            Object::Integer(_) => "Integer",
+            Object::Real(_) => "Real",
+            Object::String(_) => "String",
+            Object::Array(_) => "Array",
+            Object::Null(_) => "Null",
+            Object::Dictionary(_) => "Dictionary",
+            Object::Stream(_) => "Stream",
+            Object::Reference(_) => "Reference",
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, Object::Null(_))
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        let b: &Boolean = self.get_obj()?;
+        Some(b.get())
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        let i: &Integer = self.get_obj()?;
+        Some(i.get())
+    }
+    /// The value of a real or an integer, as numbers may be written either way.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Object::Integer(i) => Some(i.as_f64()),
+            Object::Real(r) => Some(r.get()),
+            _ => None,
+        }
+    }
+    /// The decoded bytes of a name.
+    pub fn as_name(&self) -> Option<&[u8]> {
+        let n: &Name = self.get_obj()?;
+        Some(n.get())
+    }
+    /// The raw bytes of a string, see [`String::to_text`] for text strings.
+    pub fn as_string(&self) -> Option<&[u8]> {
+        let s: &String = self.get_obj()?;
+        Some(s.get())
+    }
+    pub fn as_reference(&self) -> Option<ObjectId> {
+        let r: &Reference = self.get_obj()?;
+        Some(r.get())
+    }
+    pub fn as_array(&self) -> Option<&Array<'b>> {
+        self.get_obj()
+    }
+    pub fn as_array_mut(&mut self) -> Option<&mut Array<'b>> {
+        self.get_obj_mut()
+    }
+    pub fn as_dict(&self) -> Option<&Dictionary<'b>> {
+        self.get_obj()
+    }
+    pub fn as_dict_mut(&mut self) -> Option<&mut Dictionary<'b>> {
+        self.get_obj_mut()
+    }
+    pub fn as_stream(&self) -> Option<&Stream<'b>> {
+        self.get_obj()
+    }
+    pub fn as_stream_mut(&mut self) -> Option<&mut Stream<'b>> {
+        self.get_obj_mut()
+    }
+    /// Detaches the object from the buffer it was parsed from.
+    pub fn into_owned(self) -> Object<'static> {
+        match self {
+            Object::Boolean(b) => Object::Boolean(b),
+            Object::Name(n) => Object::Name(n.into_owned()),
+            Object::Integer(i) => Object::Integer(i),
+            Object::Real(r) => Object::Real(r),
+            Object::String(s) => Object::String(s),
+            Object::Array(a) => Object::Array(a.into_owned()),
+            Object::Null(n) => Object::Null(n),
+            Object::Dictionary(d) => Object::Dictionary(d.into_owned()),
+            Object::Stream(s) => Object::Stream(s.into_owned()),
+            Object::Reference(r) => Object::Reference(r),
+        }
+    }
+}
+
+/// The limits for the content of an array or dictionary starting at `input`.
+fn nested(input: &[u8], limits: &Limits) -> Result<Limits, nom::Err<ParseError>> {
+    limits
+        .nested()
+        .ok_or_else(|| nom::Err::Failure(ParseError::with_reason(input, "nesting too deep")))
+}
+
+macro_rules! impl_from {
+    ($obj:ident) => {
+        impl From<$obj> for Object<'_> {
+            fn from(o: $obj) -> Self {
+                Object::$obj(o)
+            }
+        }
+    };
+}
+
+macro_rules! impl_from_lt {
+    ($obj:ident) => {
+        impl<'b> From<$obj<'b>> for Object<'b> {
+            fn from(o: $obj<'b>) -> Self {
+                Object::$obj(o)
+            }
+        }
+    };
+}
+impl_from_lt!(Array);
+impl_from_lt!(Dictionary);
+impl_from_lt!(Stream);
+impl_from!(Boolean);
+impl_from_lt!(Name);
+impl_from!(Integer);
+impl_from!(Real);
+impl_from!(String);
+impl_from!(Null);
+impl_from!(Reference);
+
+impl From<bool> for Object<'_> {
+    fn from(b: bool) -> Self {
+        Object::Boolean(Boolean::new(b))
+    }
+}
+
+impl From<i64> for Object<'_> {
+    fn from(i: i64) -> Self {
+        Object::Integer(Integer::new(i))
+    }
+}
+
+impl From<f64> for Object<'_> {
+    fn from(r: f64) -> Self {
+        Object::Real(Real::new(r))
+    }
+}
+
+impl From<ObjectId> for Object<'_> {
+    fn from(id: ObjectId) -> Self {
+        Object::Reference(Reference::new(id))
+    }
 }
 
 pub trait GetObj<T> {
     fn get_obj(&self) -> Option<&T>;
+    fn get_obj_mut(&mut self) -> Option<&mut T>;
 }
 
 macro_rules! impl_get_obj {
@@ -64,6 +256,24 @@ macro_rules! impl_get_obj {
                 }
                 None
             }
+            fn get_obj_mut(&mut self) -> Option<&mut $obj> {
+                if let Object::$obj(o) = self {
+                    return Some(o);
+                }
+                None
+            }
+        }
+        impl TryFrom<Object<'_>> for $obj {
+            type Error = Error;
+            fn try_from(o: Object<'_>) -> Result<Self, Error> {
+                match o {
+                    Object::$obj(o) => Ok(o),
+                    o => Err(Error::UnexpectedType {
+                        expected: stringify!($obj),
+                        found: o.type_name(),
+                    }),
+                }
+            }
         }
     };
 }
@@ -77,6 +287,24 @@ macro_rules! impl_get_obj_lt {
                 }
                 None
             }
+            fn get_obj_mut(&mut self) -> Option<&mut $obj<'b>> {
+                if let Object::$obj(o) = self {
+                    return Some(o);
+                }
+                None
+            }
+        }
+        impl<'b> TryFrom<Object<'b>> for $obj<'b> {
+            type Error = Error;
+            fn try_from(o: Object<'b>) -> Result<Self, Error> {
+                match o {
+                    Object::$obj(o) => Ok(o),
+                    o => Err(Error::UnexpectedType {
+                        expected: stringify!($obj),
+                        found: o.type_name(),
+                    }),
+                }
+            }
         }
     };
 }
@@ -89,6 +317,38 @@ impl_get_obj!(Integer);
 impl_get_obj!(Real);
 impl_get_obj!(String);
 impl_get_obj!(Null);
+impl_get_obj!(Reference);
+
+impl TryFrom<Object<'_>> for bool {
+    type Error = Error;
+    fn try_from(o: Object<'_>) -> Result<Self, Error> {
+        Boolean::try_from(o).map(|b| b.get())
+    }
+}
+
+impl TryFrom<Object<'_>> for i64 {
+    type Error = Error;
+    fn try_from(o: Object<'_>) -> Result<Self, Error> {
+        Integer::try_from(o).map(|i| i.get())
+    }
+}
+
+impl TryFrom<Object<'_>> for f64 {
+    type Error = Error;
+    fn try_from(o: Object<'_>) -> Result<Self, Error> {
+        o.as_f64().ok_or(Error::UnexpectedType {
+            expected: "number",
+            found: o.type_name(),
+        })
+    }
+}
+
+impl TryFrom<Object<'_>> for ObjectId {
+    type Error = Error;
+    fn try_from(o: Object<'_>) -> Result<Self, Error> {
+        Reference::try_from(o).map(|r| r.get())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -172,4 +432,179 @@ mod tests {
         let data = stream.get_data();
         assert_eq!(data, b"stream");
     }
+    #[test]
+    fn parse_reference() {
+        let (rem, obj) = Object::parse(b"12 0 R").unwrap();
+        assert!(rem.is_empty());
+        let obj: &Reference = obj.get_obj().unwrap();
+        assert_eq!(obj.get(), ObjectId::new(12, 0))
+    }
+    #[test]
+    fn parse_integer_not_reference() {
+        let (rem, obj) = Object::parse(b"12 0 obj").unwrap();
+        assert_eq!(rem, b" 0 obj");
+        let obj: &Integer = obj.get_obj().unwrap();
+        assert_eq!(obj.get(), 12)
+    }
+    #[test]
+    fn write_object_1() {
+        let inputs: [&[u8]; 6] = [
+            b"true",
+            b"/name",
+            b"-5",
+            b"[1 0 R (a\\)b) <00ff>]",
+            b"<</A [0.5]>>",
+            b"<</Length 2>>\nstream\n\x00\xff\nendstream",
+        ];
+        for input in inputs {
+            let (_, parsed) = Object::parse(input).unwrap();
+            let mut out = Vec::new();
+            parsed.write_to(&mut out).unwrap();
+            assert_eq!(out, input);
+        }
+    }
+    #[test]
+    fn build_object_1() {
+        let mut page = Dictionary::new();
+        page.insert(Name::new(b"Type"), Name::new(b"Page").into());
+        page.insert(Name::new(b"Parent"), ObjectId::new(2, 0).into());
+        page.insert(
+            Name::new(b"MediaBox"),
+            Array::new(vec![0.into(), 0.into(), 612.into(), 792.5.into()]).into(),
+        );
+        page.insert(Name::new_owned(b"Custom Key".to_vec()), true.into());
+        let mut out = Vec::new();
+        Object::from(page).write_to(&mut out).unwrap();
+        assert_eq!(
+            out,
+            b"<</Type /Page /Parent 2 0 R /MediaBox [0 0 612 792.5] /Custom#20Key true>>"
+        );
+    }
+    #[test]
+    fn build_object_2() {
+        let stream = Stream::new(Dictionary::new(), b"BT ET".to_vec()).unwrap();
+        let length: &Integer = stream
+            .get_info()
+            .get(&Name::new(b"Length"))
+            .unwrap()
+            .get_obj()
+            .unwrap();
+        assert_eq!(length.get(), 5);
+        let indirect = IndirectObject::new(ObjectId::new(4, 0), stream.into());
+        let mut out = Vec::new();
+        indirect.write_to(&mut out).unwrap();
+        assert_eq!(
+            out,
+            b"4 0 obj\n<</Length 5>>\nstream\nBT ET\nendstream\nendobj\n"
+        );
+    }
+    #[test]
+    fn owned_object_1() {
+        let owned: Object<'static> = {
+            let input = b"<</Type /Page /Contents [<</Length 2>>] /S (x)>>".to_vec();
+            let (_, parsed) = Object::parse(&input).unwrap();
+            parsed.into_owned()
+        };
+        let mut owned = owned;
+        if let Object::Dictionary(d) = &mut owned {
+            d.insert(Name::new_owned(b"Rotate".to_vec()), 90.into());
+        }
+        let mut out = Vec::new();
+        owned.write_to(&mut out).unwrap();
+        assert_eq!(
+            out,
+            b"<</Type /Page /Contents [<</Length 2>>] /S (x) /Rotate 90>>"
+        );
+    }
+    #[test]
+    fn parse_integer_overflow() {
+        let (rem, obj) = Object::parse(b"99999999999999999999 ").unwrap();
+        assert_eq!(rem, b" ");
+        let obj: &Real = obj.get_obj().unwrap();
+        assert_eq!(obj.get(), 1e20);
+        let (_, obj) = Object::parse(b"-9223372036854775807").unwrap();
+        let obj: &Integer = obj.get_obj().unwrap();
+        assert_eq!(obj.get(), -i64::MAX);
+    }
+    #[test]
+    fn parse_deep_nesting() {
+        let input = [b'['; 10000];
+        let err = match Object::parse(&input) {
+            Err(nom::Err::Failure(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "nesting too deep");
+        let input = "<</A ".repeat(5000);
+        assert!(Object::parse(input.as_bytes()).is_err());
+        let limits = Limits {
+            max_depth: 2,
+            ..Limits::default()
+        };
+        assert!(Object::parse_limited(b"[[1]]", &limits).is_ok());
+        assert!(Object::parse_limited(b"[<</A [1]>>]", &limits).is_err());
+    }
+    #[test]
+    fn parse_nested_dictionaries() {
+        // Each level is parsed once, not once as a stream and again as a dictionary.
+        let depth = 60;
+        let mut input = "<</A ".repeat(depth);
+        input.push('1');
+        input.push_str(&">>".repeat(depth));
+        let (rem, obj) = Object::parse(input.as_bytes()).unwrap();
+        assert!(rem.is_empty());
+        assert!(matches!(obj, Object::Dictionary(_)));
+    }
+    #[test]
+    fn typed_accessors() {
+        let (_, obj) =
+            Object::parse(b"<</N 3 /R 2.5 /T /Page /B true /K [1 0 R] /S (x)>>").unwrap();
+        let dict = obj.as_dict().unwrap();
+        let get = |key: &[u8]| dict.get(&Name::new(key)).unwrap();
+        assert_eq!(get(b"N").as_i64(), Some(3));
+        assert_eq!(get(b"N").as_f64(), Some(3.0));
+        assert_eq!(get(b"R").as_f64(), Some(2.5));
+        assert_eq!(get(b"R").as_i64(), None);
+        assert_eq!(get(b"T").as_name(), Some(b"Page".as_slice()));
+        assert_eq!(get(b"B").as_bool(), Some(true));
+        assert_eq!(get(b"S").as_string(), Some(b"x".as_slice()));
+        let kids = get(b"K").as_array().unwrap();
+        assert_eq!(kids.get()[0].as_reference(), Some(ObjectId::new(1, 0)));
+        assert!(obj.as_array().is_none());
+        assert_eq!(obj.type_name(), "Dictionary");
+    }
+    #[test]
+    fn mutable_accessors() {
+        let (_, mut obj) = Object::parse(b"<</Kids [1]>>").unwrap();
+        let dict = obj.as_dict_mut().unwrap();
+        dict.insert(Name::new(b"Count"), 1.into());
+        let kids = dict.get_mut(&Name::new(b"Kids")).unwrap();
+        kids.as_array_mut().unwrap().push(2.into());
+        let integer: &mut Integer = kids.as_array_mut().unwrap().get_mut()[0]
+            .get_obj_mut()
+            .unwrap();
+        *integer = Integer::new(7);
+        let mut out = Vec::new();
+        obj.write_to(&mut out).unwrap();
+        assert_eq!(out, b"<</Kids [7 2] /Count 1>>");
+    }
+    #[test]
+    fn try_from_object() {
+        assert_eq!(i64::try_from(Object::from(5)), Ok(5));
+        assert_eq!(f64::try_from(Object::from(5)), Ok(5.0));
+        assert_eq!(bool::try_from(Object::from(true)), Ok(true));
+        let id = ObjectId::new(3, 0);
+        assert_eq!(ObjectId::try_from(Object::from(id)), Ok(id));
+        let name = Name::try_from(Object::from(Name::new(b"A"))).unwrap();
+        assert_eq!(name.get(), b"A");
+        assert_eq!(
+            Dictionary::try_from(Object::from(1.5)).unwrap_err(),
+            Error::UnexpectedType {
+                expected: "Dictionary",
+                found: "Real"
+            }
+        );
+        let err = f64::try_from(Object::Null(Null)).unwrap_err();
+        assert_eq!(err.to_string(), "expected number, found Null");
+    }
 }
@@ -1,24 +1,118 @@
-use nom::{
-    IResult, Parser, bytes::complete::take_while1, character::complete::char, sequence::preceded,
+use std::{
+    borrow::{Borrow, Cow},
+    hash::{Hash, Hasher},
+    io::{self, Write},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Name<'b>(&'b [u8]);
+use nom::{Parser, bytes::complete::take_while1, character::complete::char, sequence::preceded};
+
+use crate::{
+    error::{ParseError, ParseResult},
+    lexer::is_regular,
+};
+
+/// A name object. Names compare and hash by their decoded bytes; the spelling found in the file is
+/// kept when it contained `#xx` escapes.
+#[derive(Debug, Clone)]
+pub struct Name<'b> {
+    name: Cow<'b, [u8]>,
+    raw: Option<Cow<'b, [u8]>>,
+}
 
 impl<'b> Name<'b> {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], Name> {
-        preceded(
-            char('/'),
-            take_while1(|c: u8| !b"\n\r%()<>[]{} ".contains(&c)),
-        )
-        .map(Name)
-        .parse(input)
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Name<'b>> {
+        let (rem, raw) = preceded(char('/'), take_while1(is_regular)).parse(input)?;
+        let name = Self::decode(raw).ok_or_else(|| {
+            nom::Err::Error(ParseError::with_reason(input, "invalid #xx escape in name"))
+        })?;
+        Ok((rem, name))
+    }
+    /// Decodes the `#xx` escapes of a name token, rejecting escapes that are not followed by two
+    /// hexadecimal digits or that encode the null character.
+    fn decode(raw: &'b [u8]) -> Option<Self> {
+        if !raw.contains(&b'#') {
+            return Some(Self::new(raw));
+        }
+        let mut name = Vec::with_capacity(raw.len());
+        let mut bytes = raw.iter();
+        while let Some(b) = bytes.next() {
+            if *b == b'#' {
+                let digits = [*bytes.next()?, *bytes.next()?];
+                let decoded = u8::from_str_radix(core::str::from_utf8(&digits).ok()?, 16).ok()?;
+                if decoded == 0 || !digits.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                name.push(decoded);
+            } else {
+                name.push(*b);
+            }
+        }
+        Some(Self {
+            name: Cow::Owned(name),
+            raw: Some(Cow::Borrowed(raw)),
+        })
     }
-    pub fn get(&self) -> &'b [u8] {
-        self.0
+    pub fn get(&self) -> &[u8] {
+        &self.name
+    }
+    /// The name as it was spelled in the file, escapes included, without the leading `/`.
+    pub fn get_raw(&self) -> &[u8] {
+        self.raw.as_deref().unwrap_or(&self.name)
     }
     pub fn new(name: &'b [u8]) -> Self {
-        Self(name)
+        Self {
+            name: Cow::Borrowed(name),
+            raw: None,
+        }
+    }
+    pub fn new_owned(name: Vec<u8>) -> Name<'static> {
+        Name {
+            name: Cow::Owned(name),
+            raw: None,
+        }
+    }
+    pub fn into_owned(self) -> Name<'static> {
+        Name {
+            name: Cow::Owned(self.name.into_owned()),
+            raw: self.raw.map(|raw| Cow::Owned(raw.into_owned())),
+        }
+    }
+    /// Writes the name with its original spelling if it was parsed with escapes, otherwise with
+    /// every delimiter, whitespace, `#` and non-printable byte escaped as `#xx`.
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"/")?;
+        if let Some(raw) = &self.raw {
+            return w.write_all(raw);
+        }
+        for b in self.name.iter() {
+            if (b'!'..=b'~').contains(b) && !b"#%()/<>[]{}".contains(b) {
+                w.write_all(&[*b])?;
+            } else {
+                write!(w, "#{b:02X}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Name<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Name<'_> {}
+
+impl Hash for Name<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+/// Lets dictionaries be searched with a name of any lifetime.
+impl Borrow<[u8]> for Name<'_> {
+    fn borrow(&self) -> &[u8] {
+        &self.name
     }
 }
 
@@ -49,4 +143,74 @@ mod tests {
         assert_eq!(rem, b" ");
         assert_eq!(parsed.get(), b"A")
     }
+    #[test]
+    fn write_name_1() {
+        let mut out = Vec::new();
+        Name::new(b"A;Name_With-various***characters?")
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, b"/A;Name_With-various***characters?");
+    }
+    #[test]
+    fn write_name_2() {
+        let mut out = Vec::new();
+        Name::new(b"Lime Green#(1)/\xe9")
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, b"/Lime#20Green#23#281#29#2F#E9");
+    }
+    #[test]
+    fn write_name_3() {
+        let mut out = Vec::new();
+        Name::new(b"").write_to(&mut out).unwrap();
+        assert_eq!(out, b"/");
+    }
+    #[test]
+    fn parse_name_5() {
+        let (rem, parsed) = Name::parse(b"/Type/Page").unwrap();
+        assert_eq!(rem, b"/Page");
+        assert_eq!(parsed.get(), b"Type");
+        let (rem, parsed) = Name::parse(b"/A\x00\tB").unwrap();
+        assert_eq!(rem, b"\x00\tB");
+        assert_eq!(parsed.get(), b"A");
+    }
+    #[test]
+    fn parse_name_escape_1() {
+        let (rem, parsed) = Name::parse(b"/Lime#20Green ").unwrap();
+        assert_eq!(rem, b" ");
+        assert_eq!(parsed.get(), b"Lime Green");
+        assert_eq!(parsed.get_raw(), b"Lime#20Green");
+    }
+    #[test]
+    fn parse_name_escape_2() {
+        let (_, escaped) = Name::parse(b"/#41#42c").unwrap();
+        let (_, plain) = Name::parse(b"/ABc").unwrap();
+        assert_eq!(escaped, plain);
+        assert_eq!(plain.get_raw(), b"ABc");
+        assert!(matches!(plain.name, Cow::Borrowed(_)));
+        let mut out = Vec::new();
+        escaped.write_to(&mut out).unwrap();
+        assert_eq!(out, b"/#41#42c");
+    }
+    #[test]
+    fn parse_name_escape_3() {
+        let (_, parsed) = Name::parse(b"/paired#28#29parentheses#2fand#2Fslash").unwrap();
+        assert_eq!(parsed.get(), b"paired()parentheses/and/slash");
+        let owned = parsed.clone().into_owned();
+        assert_eq!(owned, parsed);
+        assert_eq!(owned.get_raw(), parsed.get_raw());
+    }
+    #[test]
+    fn parse_name_escape_4() {
+        assert!(Name::parse(b"/A#2").is_err());
+        assert!(Name::parse(b"/A#").is_err());
+        assert!(Name::parse(b"/A#zz").is_err());
+        assert!(Name::parse(b"/A#+1").is_err());
+        assert!(Name::parse(b"/A#00").is_err());
+    }
+    #[test]
+    fn name_lookup_1() {
+        let (_, parsed) = crate::objects::Dictionary::parse(b"<</Font#20Name 1>>").unwrap();
+        assert!(parsed.contains_key(&Name::new(b"Font Name")));
+    }
 }
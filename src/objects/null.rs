@@ -1,11 +1,18 @@
-use nom::{IResult, Parser, bytes::complete::tag};
+use std::io::{self, Write};
+
+use nom::Parser;
+
+use crate::{error::ParseResult, lexer::keyword};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Null;
 
 impl Null {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], Null> {
-        tag("null").map(|_| Null).parse(input)
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Null> {
+        keyword("null").map(|_| Null).parse(input)
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"null")
     }
 }
 
@@ -23,4 +30,16 @@ mod tests {
         let parsed = Null::parse(b"nul");
         assert!(parsed.is_err())
     }
+    #[test]
+    fn write_null_1() {
+        let mut out = Vec::new();
+        Null.write_to(&mut out).unwrap();
+        assert_eq!(out, b"null");
+    }
+    #[test]
+    fn parse_null_keyword_1() {
+        assert!(Null::parse(b"nullify").is_err());
+        let (rem, _) = Null::parse(b"null>>").unwrap();
+        assert_eq!(rem, b">>");
+    }
 }
@@ -1,20 +1,24 @@
+use std::io::{self, Write};
+
 use nom::{
-    IResult, Parser,
+    Parser,
     branch::alt,
     bytes::complete::take_while,
     character::complete::{char, digit0, digit1},
     combinator::{map, opt, recognize, verify},
     sequence::pair,
 };
+
+use crate::error::ParseResult;
 #[derive(Debug, Clone, Copy, PartialEq)]
 
-pub struct Integer(i32);
+pub struct Integer(i64);
 #[derive(Debug, Clone, Copy, PartialEq)]
 
-pub struct Real(f32);
+pub struct Real(f64);
 
 impl Integer {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], Integer> {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Integer> {
         let sign = opt(alt((char('+'), char('-'))));
         let parse_number = verify(
             take_while(|c: u8| c.is_ascii_digit() || c == b'.'),
@@ -26,16 +30,26 @@ impl Integer {
             .map_opt(|(sign, value)| sign_integer(sign, value))
             .parse(input)
     }
-    pub fn get(&self) -> i32 {
+    pub fn get(&self) -> i64 {
         self.0
     }
-    pub fn new(i: i32) -> Self {
+    pub fn new(i: i64) -> Self {
         Self(i)
     }
+    /// The value as the nearest `f64`, as a real would be.
+    pub fn as_f64(&self) -> f64 {
+        // Both halves convert exactly, so the sum is rounded only once.
+        let high = i32::try_from(self.0.wrapping_shr(32)).unwrap_or_default();
+        let low = u32::try_from(self.0 & 0xffff_ffff).unwrap_or_default();
+        f64::from(high) * 4_294_967_296.0 + f64::from(low)
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(w, "{}", self.0)
+    }
 }
 
 impl Real {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], Real> {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Real> {
         let sign = opt(alt((char('+'), char('-'))));
         let parse_float = recognize(alt((
             map((digit1, opt((char('.'), digit0))), |_| ()),
@@ -48,28 +62,37 @@ impl Real {
             .map(|(rem, (s, v))| (rem, sign_real(s, v)))
     }
 
-    pub fn get(&self) -> f32 {
+    pub fn get(&self) -> f64 {
         self.0
     }
-    pub fn new(r: f32) -> Self {
+    pub fn new(r: f64) -> Self {
         Self(r)
     }
+    /// Writes the number in plain decimal notation, with a decimal point so it reads back as a
+    /// real. Non-finite values, which PDF cannot represent, are written as `0.0`.
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        if !self.0.is_finite() {
+            return w.write_all(b"0.0");
+        }
+        let s = self.0.to_string();
+        w.write_all(s.as_bytes())?;
+        if !s.contains('.') {
+            w.write_all(b".0")?;
+        }
+        Ok(())
+    }
 }
 
-fn sign_integer(sign: Option<char>, value: i32) -> Option<Integer> {
-    if let Some(s) = sign {
-        if s == '-' {
-            return Some(Integer(value.checked_neg()?));
-        }
+fn sign_integer(sign: Option<char>, value: i64) -> Option<Integer> {
+    if let Some('-') = sign {
+        return Some(Integer(value.checked_neg()?));
     }
     Some(Integer(value))
 }
 
-fn sign_real(sign: Option<char>, value: f32) -> Real {
-    if let Some(s) = sign {
-        if s == '-' {
-            return Real(-value);
-        }
+fn sign_real(sign: Option<char>, value: f64) -> Real {
+    if let Some('-') = sign {
+        return Real(-value);
     }
     Real(value)
 }
@@ -77,6 +100,13 @@ fn sign_real(sign: Option<char>, value: f32) -> Real {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn integer_as_f64() {
+        assert_eq!(Integer::new(-3).as_f64(), -3.0);
+        assert_eq!(Integer::new(1 << 40).as_f64(), 1_099_511_627_776.0);
+        assert_eq!(Integer::new(i64::MIN).as_f64(), -(2f64.powi(63)));
+        assert_eq!(Integer::new(i64::MAX).as_f64(), 2f64.powi(63));
+    }
 
     #[test]
     fn parse_integer_1() {
@@ -164,4 +194,61 @@ mod tests {
         assert_eq!(rem, b"e-1");
         assert_eq!(parsed.0, 1.2);
     }
+    #[test]
+    fn write_integer_1() {
+        let mut out = Vec::new();
+        Integer(-98).write_to(&mut out).unwrap();
+        assert_eq!(out, b"-98");
+    }
+    #[test]
+    fn write_real_1() {
+        let mut out = Vec::new();
+        Real(-34.5).write_to(&mut out).unwrap();
+        assert_eq!(out, b"-34.5");
+    }
+    #[test]
+    fn write_real_2() {
+        let mut out = Vec::new();
+        Real(3.0).write_to(&mut out).unwrap();
+        assert_eq!(out, b"3.0");
+        let (rem, parsed) = Real::parse(&out).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed, Real(3.0));
+    }
+    #[test]
+    fn write_real_3() {
+        for r in [0.000_001_5, 1e20, f64::MIN_POSITIVE] {
+            let mut out = Vec::new();
+            Real(r).write_to(&mut out).unwrap();
+            assert!(!out.contains(&b'e'));
+            let (rem, parsed) = Real::parse(&out).unwrap();
+            assert!(rem.is_empty());
+            assert_eq!(parsed, Real(r));
+        }
+    }
+    #[test]
+    fn write_real_4() {
+        let mut out = Vec::new();
+        Real(f64::NAN).write_to(&mut out).unwrap();
+        assert_eq!(out, b"0.0");
+    }
+    #[test]
+    fn parse_integer_7() {
+        let (rem, parsed) = Integer::parse(b"-5000000000").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.0, -5_000_000_000);
+    }
+    #[test]
+    fn parse_integer_8() {
+        assert!(Integer::parse(b"99999999999999999999").is_err());
+    }
+    #[test]
+    fn parse_real_precision_1() {
+        let (rem, parsed) = Real::parse(b"612.123456789012").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.0, 612.123456789012);
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, b"612.123456789012");
+    }
 }
@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+
+use nom::{Parser, sequence::terminated};
+
+use crate::{
+    error::ParseResult,
+    lexer::{keyword, parse_unsigned, space1},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId {
+    number: u32,
+    generation: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reference(ObjectId);
+
+impl ObjectId {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, ObjectId> {
+        (
+            terminated(parse_unsigned::<u32>, space1),
+            parse_unsigned::<u16>,
+        )
+            .map(|(number, generation)| ObjectId { number, generation })
+            .parse(input)
+    }
+    pub fn new(number: u32, generation: u16) -> Self {
+        Self { number, generation }
+    }
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+    pub fn generation(&self) -> u16 {
+        self.generation
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(w, "{} {}", self.number, self.generation)
+    }
+}
+
+impl Reference {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, Reference> {
+        terminated(ObjectId::parse, (space1, keyword("R")))
+            .map(Reference)
+            .parse(input)
+    }
+    pub fn get(&self) -> ObjectId {
+        self.0
+    }
+    pub fn new(id: ObjectId) -> Self {
+        Self(id)
+    }
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        self.0.write_to(w)?;
+        w.write_all(b" R")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reference_1() {
+        let (rem, parsed) = Reference::parse(b"12 0 R").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get(), ObjectId::new(12, 0));
+    }
+    #[test]
+    fn parse_reference_2() {
+        let (rem, parsed) = Reference::parse(b"7 3\nR ").unwrap();
+        assert_eq!(rem, b" ");
+        assert_eq!(parsed.get().number(), 7);
+        assert_eq!(parsed.get().generation(), 3);
+    }
+    #[test]
+    fn parse_reference_3() {
+        let parsed = Reference::parse(b"12 0");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn parse_reference_4() {
+        let parsed = Reference::parse(b"-12 0 R");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn parse_reference_5() {
+        let parsed = Reference::parse(b"1 70000 R");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn write_reference_1() {
+        let mut out = Vec::new();
+        Reference::new(ObjectId::new(12, 3))
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, b"12 3 R");
+    }
+    #[test]
+    fn parse_reference_keyword_1() {
+        assert!(Reference::parse(b"1 0 RG").is_err());
+        let (rem, parsed) = Reference::parse(b"1 %c\n0\x00R]").unwrap();
+        assert_eq!(rem, b"]");
+        assert_eq!(parsed.get(), ObjectId::new(1, 0));
+    }
+}
@@ -1,51 +1,162 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
 use nom::{
-    IResult, Parser,
-    bytes::complete::tag,
-    character::complete::{multispace0, multispace1},
-    sequence::{delimited, preceded, terminated},
+    Parser,
+    branch::alt,
+    bytes::complete::{tag, take_while},
+    sequence::{delimited, preceded},
 };
 
-use super::{Dictionary, GetObj, Integer, Name};
+use super::{Dictionary, GetObj, Integer, Name, Object};
+use crate::{
+    error::{Error, ParseError, ParseResult, Result},
+    filters::filter_chain,
+    lexer::{keyword, space0},
+    limits::Limits,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stream<'b> {
     pub info: Dictionary<'b>,
-    pub data: &'b [u8],
+    pub data: Cow<'b, [u8]>,
 }
 
 impl<'b> Stream<'b> {
-    pub fn parse(input: &'b [u8]) -> IResult<&'b [u8], Stream<'b>> {
-        let (rem, stream) = terminated(
-            Dictionary::parse,
-            delimited(multispace0, tag("stream"), multispace1),
-        )
-        .parse(input)
-        .map(|(rem, info)| {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Stream<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, Stream<'b>> {
+        let (rem, info) = Dictionary::parse_limited(input, limits)?;
+        let (data, ()) = Self::parse_keyword(rem)?;
+        Self::parse_data(info, data)
+    }
+    /// Parses the `stream` keyword following the dictionary and the end-of-line marker after it.
+    pub fn parse_keyword(input: &'b [u8]) -> ParseResult<'b, ()> {
+        delimited(space0, keyword("stream"), stream_eol)
+            .map(|_| ())
+            .parse(input)
+    }
+    /// Parses the data of a stream described by `info` up to and including `endstream`.
+    pub fn parse_data(info: Dictionary<'b>, input: &'b [u8]) -> ParseResult<'b, Stream<'b>> {
+        Self::parse_data_with_length(info, input, None)
+    }
+    /// Parses the data of a stream whose length is `length`, or the `/Length` of `info` if that is
+    /// a direct integer. When the length does not end the data right before `endstream`, which is
+    /// common in damaged files, the data extends to the next `endstream` instead.
+    pub fn parse_data_with_length(
+        info: Dictionary<'b>,
+        input: &'b [u8],
+        length: Option<usize>,
+    ) -> ParseResult<'b, Stream<'b>> {
+        let length = length.or_else(|| {
             let length: &Integer = info.get(&Name::new(b"Length"))?.get_obj()?;
-            let length: usize = length.get().try_into().ok()?;
-            let data = rem.get(0..(length))?;
-            let rem = rem.get(length..)?;
-
-            Some((rem, Stream { info, data }))
-        })?
-        .ok_or(nom::Err::Error(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::TakeWhileMN,
-        )))?;
-
-        preceded(multispace0, tag("endstream"))
-            .map(|_| stream.clone())
-            .parse(rem)
+            length.get().try_into().ok()
+        });
+        let declared = length.and_then(|length| {
+            let (data, rem) = input.split_at_checked(length)?;
+            let (rem, _) = end_of_data(rem).ok()?;
+            Some((rem, data))
+        });
+        let (rem, data) = declared.or_else(|| find_endstream(input)).ok_or_else(|| {
+            nom::Err::Error(ParseError::with_reason(
+                input,
+                "missing endstream after the stream data",
+            ))
+        })?;
+        Ok((
+            rem,
+            Stream {
+                info,
+                data: Cow::Borrowed(data),
+            },
+        ))
     }
 
+    /// Creates a stream from already encoded data, setting its `/Length`. Fails if the length
+    /// does not fit in an integer object.
+    pub fn new(mut info: Dictionary<'b>, data: impl Into<Cow<'b, [u8]>>) -> Result<Self> {
+        let data = data.into();
+        let length =
+            i64::try_from(data.len()).map_err(|_| Error::LimitExceeded("stream length"))?;
+        info.insert(Name::new(b"Length"), Object::Integer(Integer::new(length)));
+        Ok(Self { info, data })
+    }
     pub fn get_info(&self) -> &Dictionary<'b> {
         &self.info
     }
-    pub fn get_data(&self) -> &'b [u8] {
-        self.data
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Applies the `/Filter` chain of the stream to its data.
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        self.decode_limited(&Limits::default())
+    }
+    /// Applies the `/Filter` chain of the stream to its data, failing if the result would exceed
+    /// the decoded size or ratio allowed by `limits`.
+    pub fn decode_limited(&self, limits: &Limits) -> Result<Vec<u8>> {
+        let limit = limits.decoded_size(self.data.len());
+        let mut data = self.data.to_vec();
+        for (filter, parms) in filter_chain(&self.info)? {
+            data = filter.decode_limited(&data, parms, limit)?;
+        }
+        Ok(data)
+    }
+    /// Writes the stream with its `/Length` set to the length of the data.
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let length = i64::try_from(self.data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "stream is too long"))?;
+        let mut info = self.info.clone();
+        info.insert(Name::new(b"Length"), Object::Integer(Integer::new(length)));
+        info.write_to(w)?;
+        w.write_all(b"\nstream\n")?;
+        w.write_all(&self.data)?;
+        w.write_all(b"\nendstream")
+    }
+    pub fn into_owned(self) -> Stream<'static> {
+        Stream {
+            info: self.info.into_owned(),
+            data: Cow::Owned(self.data.into_owned()),
+        }
     }
 }
 
+fn end_of_data(input: &[u8]) -> ParseResult<'_, &[u8]> {
+    preceded(space0, keyword("endstream")).parse(input)
+}
+
+/// Searches for the first `endstream` keyword and returns the input after it along with the data
+/// before it, minus the end-of-line marker that should precede `endstream`.
+fn find_endstream(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    let needle = b"endstream";
+    input
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .find_map(|(offset, _)| {
+            let (data, rest) = input.split_at_checked(offset)?;
+            let (rem, _) = keyword("endstream").parse(rest).ok()?;
+            let data = data
+                .strip_suffix(b"\r\n")
+                .or_else(|| data.strip_suffix(b"\n"))
+                .or_else(|| data.strip_suffix(b"\r"))
+                .unwrap_or(data);
+            Some((rem, data))
+        })
+}
+
+/// The end-of-line marker after the `stream` keyword. Trailing spaces and a lone carriage return
+/// are tolerated although the specification requires CRLF or LF.
+fn stream_eol(input: &[u8]) -> ParseResult<'_, &[u8]> {
+    preceded(
+        take_while(|c| c == b' '),
+        alt((tag("\r\n"), tag("\n"), tag("\r"))),
+    )
+    .parse(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,5 +204,114 @@ mod tests {
         assert_eq!(length.get(), 59);
         let parsed_data = parsed.get_data();
         assert_eq!(parsed_data, stream_data);
+        let decoded = parsed.decode().unwrap();
+        assert_eq!(decoded, stream_data);
+    }
+    #[test]
+    fn stream_decode_1() {
+        let mut input = b"<</Length 13 /Filter /FlateDecode>>stream\n".to_vec();
+        input.extend_from_slice(&[
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x15,
+        ]);
+        input.extend_from_slice(b"\nendstream");
+        let (_, parsed) = Stream::parse(&input).unwrap();
+        assert_eq!(parsed.decode().unwrap(), b"hello");
+    }
+    #[test]
+    fn stream_decode_2() {
+        let mut input =
+            b"<</Length 14 /Filter [/FlateDecode] /DecodeParms [<</Predictor 2 /Columns 3>>]>>stream\n"
+                .to_vec();
+        input.extend_from_slice(&[
+            0x78, 0x9c, 0x63, 0x64, 0x62, 0x66, 0x61, 0x64, 0x04, 0x00, 0x00, 0x31, 0x00, 0x0d,
+        ]);
+        input.extend_from_slice(b"\nendstream");
+        let (_, parsed) = Stream::parse(&input).unwrap();
+        assert_eq!(parsed.decode().unwrap(), [1, 3, 6, 4, 5, 6]);
+    }
+    #[test]
+    fn write_stream_1() {
+        let input = b"<</Length 3>>\nstream\nabc\nendstream";
+        let (_, parsed) = Stream::parse(input).unwrap();
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+    #[test]
+    fn write_stream_2() {
+        let (_, mut parsed) = Stream::parse(b"<</Length 3>>stream\nabc\nendstream").unwrap();
+        parsed.data = Cow::Borrowed(b"abcdef");
+        let mut out = Vec::new();
+        parsed.write_to(&mut out).unwrap();
+        assert_eq!(out, b"<</Length 6>>\nstream\nabcdef\nendstream");
+    }
+    #[test]
+    fn stream_eol_1() {
+        let (rem, parsed) =
+            Stream::parse(b"<</Length 3>> % c\nstream\r\n\n\tb\r\nendstream").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_data(), b"\n\tb");
+    }
+    #[test]
+    fn stream_eol_2() {
+        assert!(Stream::parse(b"<</Length 3>>streamx\nabc\nendstream").is_err());
+        assert!(Stream::parse(b"<</Length 3>>stream abc\nendstream").is_err());
+    }
+    #[test]
+    fn stream_error_1() {
+        let input = b"<</Length 3>>stream\nabc\nendobj";
+        let err = match Stream::parse(input) {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "missing endstream after the stream data");
+        assert_eq!(err.locate(input).get_offset(), Some(20));
+    }
+    #[test]
+    fn stream_wrong_length_1() {
+        let (rem, parsed) = Stream::parse(b"<</Length 100>>stream\nabc\nendstream").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_data(), b"abc");
+        let (_, parsed) = Stream::parse(b"<</Length 1>>stream\r\nab\r\nendstream").unwrap();
+        assert_eq!(parsed.get_data(), b"ab");
+        let (_, parsed) = Stream::parse(b"<</Length 1>>stream\nab endstreamx\rendstream").unwrap();
+        assert_eq!(parsed.get_data(), b"ab endstreamx");
+    }
+    #[test]
+    fn stream_wrong_length_2() {
+        // A correct length is trusted even if the data contains the keyword.
+        let (_, parsed) = Stream::parse(b"<</Length 13>>stream\na endstream b\nendstream").unwrap();
+        assert_eq!(parsed.get_data(), b"a endstream b");
+    }
+    #[test]
+    fn stream_indirect_length() {
+        let input = b"<</Length 2 0 R>>stream\nabc\nendstream";
+        let (_, parsed) = Stream::parse(input).unwrap();
+        assert_eq!(parsed.get_data(), b"abc");
+        let (_, info) = Dictionary::parse(input).unwrap();
+        let (_, parsed) =
+            Stream::parse_data_with_length(info, b"a endstream b\nendstream", Some(13)).unwrap();
+        assert_eq!(parsed.get_data(), b"a endstream b");
+    }
+    #[test]
+    fn decode_limited_1() {
+        let data = miniz_oxide::deflate::compress_to_vec_zlib(&[0; 4 << 20], 6);
+        let mut info = Dictionary::new();
+        info.insert(Name::new(b"Filter"), Name::new(b"FlateDecode").into());
+        let stream = Stream::new(info, data).unwrap();
+        assert_eq!(
+            stream.decode().unwrap_err(),
+            crate::Error::LimitExceeded("decoded stream size")
+        );
+        assert_eq!(
+            stream.decode_limited(&Limits::unlimited()).unwrap().len(),
+            4 << 20
+        );
+        let limits = Limits {
+            max_decoded_size: 1000,
+            ..Limits::unlimited()
+        };
+        assert!(stream.decode_limited(&limits).is_err());
     }
 }
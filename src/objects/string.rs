@@ -1,34 +1,92 @@
+use std::io::{self, Write};
+
 use nom::{
-    IResult, Input, Parser, branch::alt, bytes::complete::take_until, character::complete::char,
-    sequence::delimited,
+    Input, Parser,
+    branch::alt,
+    bytes::complete::take_until,
+    character::complete::char,
+    combinator::verify,
+    error::context,
+    sequence::{preceded, terminated},
+};
+
+use crate::{
+    error::{ParseError, ParseResult},
+    lexer::is_whitespace,
 };
 type RString = std::string::String;
 #[derive(Debug, PartialEq, Clone)]
 pub struct String(Vec<u8>);
 
 impl String {
-    pub fn parse(input: &[u8]) -> IResult<&[u8], String> {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, String> {
         alt((Self::parse_hexadecimal, Self::parse_literal)).parse(input)
     }
-    pub fn parse_literal(input: &[u8]) -> IResult<&[u8], String> {
-        delimited(char('('), take_until_unbalanced_bracket, char(')'))
-            .map_res(remove_esc_seq)
-            .parse(input)
+    pub fn parse_literal(input: &[u8]) -> ParseResult<'_, String> {
+        preceded(
+            char('('),
+            context(
+                "invalid escape sequence in literal string",
+                terminated(take_until_unbalanced_bracket, char(')')).map_res(remove_esc_seq),
+            ),
+        )
+        .parse(input)
     }
-    pub fn parse_hexadecimal(input: &[u8]) -> IResult<&[u8], String> {
-        delimited(char('<'), take_until(">"), char('>'))
-            .map_res(core::str::from_utf8)
-            .map(fix_hex_str)
-            .map_res(hex::decode)
-            .map(Self)
-            .parse(input)
+    pub fn parse_hexadecimal(input: &[u8]) -> ParseResult<'_, String> {
+        preceded(
+            char('<'),
+            context(
+                "invalid hexadecimal string",
+                verify(terminated(take_until(">"), char('>')), |s: &[u8]| {
+                    s.iter().all(|c| c.is_ascii_hexdigit() || is_whitespace(*c))
+                })
+                .map_res(core::str::from_utf8)
+                .map(fix_hex_str)
+                .map_res(hex::decode)
+                .map(Self),
+            ),
+        )
+        .parse(input)
     }
     pub fn get(&self) -> &[u8] {
         self.0.as_ref()
     }
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+    /// Writes the string as a literal, or in hexadecimal form when more than a quarter of its
+    /// bytes would need an escape sequence.
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let binary = self
+            .0
+            .iter()
+            .filter(|b| !(b' '..=b'~').contains(*b) && !b"\n\r\t".contains(*b))
+            .count();
+        if binary.saturating_mul(4) > self.0.len() {
+            return self.write_hexadecimal(w);
+        }
+        self.write_literal(w)
+    }
+    pub fn write_literal(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"(")?;
+        for b in &self.0 {
+            match b {
+                b'(' | b')' | b'\\' => w.write_all(&[b'\\', *b])?,
+                b'\n' => w.write_all(b"\\n")?,
+                b'\r' => w.write_all(b"\\r")?,
+                b'\t' => w.write_all(b"\\t")?,
+                b' '..=b'~' => w.write_all(&[*b])?,
+                _ => write!(w, "\\{b:03o}")?,
+            }
+        }
+        w.write_all(b")")
+    }
+    pub fn write_hexadecimal(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(w, "<{}>", hex::encode(&self.0))
+    }
 }
 
-fn take_until_unbalanced_bracket(input: &[u8]) -> IResult<&[u8], &[u8]> {
+fn take_until_unbalanced_bracket(input: &[u8]) -> ParseResult<'_, &[u8]> {
     let mut balance: i32 = 0;
     let mut is_escaped = false;
     let mut index = 0;
@@ -49,9 +107,9 @@ fn take_until_unbalanced_bracket(input: &[u8]) -> IResult<&[u8], &[u8]> {
             break;
         }
     }
-    let error = nom::Err::Error(nom::error::Error::new(
+    let error = nom::Err::Error(ParseError::with_reason(
         input,
-        nom::error::ErrorKind::TakeWhile1,
+        "unterminated literal string",
     ));
     if balance != -1 {
         return Err(error);
@@ -63,93 +121,42 @@ fn take_until_unbalanced_bracket(input: &[u8]) -> IResult<&[u8], &[u8]> {
 }
 
 fn remove_esc_seq(input: &[u8]) -> Result<String, nom::error::ErrorKind> {
-    let mut res = input.to_vec();
-    for (i, c) in input.iter_indices().rev() {
-        if c == b'\\' {
-            if let Some(esc) = input.get(i.saturating_add(1)) {
-                match esc {
-                    b'\n' | b'\r' => {
-                        res.remove(i);
-                        while let Some(nl) = res.get(i) {
-                            if *nl == b'\n' || *nl == b'\r' {
-                                res.remove(i);
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                    b'n' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, b'\n');
-                    }
-                    b'r' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, b'\r');
-                    }
-                    b't' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, b'\t');
-                    }
-                    b'b' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, 0x08);
-                    }
-                    b'f' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, 0x0C);
-                    }
-                    b'(' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, b'(');
-                    }
-                    b')' => {
-                        res.remove(i);
-                        res.remove(i);
-                        res.insert(i, b')');
-                    }
-                    b'\\' => {
-                        res.remove(i);
-                    }
-
-                    o1 @ b'0'..b'8' => {
-                        let o2 = is_ascii_digit(input.get(i.saturating_add(2)));
-                        let o3 = is_ascii_digit(input.get(i.saturating_add(3)));
-                        let mut b: u8 = 0;
-                        if o2.0 {
-                            b = 1;
-                            if o3.0 {
-                                b = 2
-                            }
-                        }
-                        let mut n = parse_octal(b, *o1)?;
-                        if o2.0 {
-                            n = n
-                                .checked_add(parse_octal(b.saturating_sub(1), o2.1)?)
-                                .ok_or(nom::error::ErrorKind::Digit)?;
-                            if o3.0 {
-                                n = n
-                                    .checked_add(parse_octal(0, o3.1)?)
-                                    .ok_or(nom::error::ErrorKind::Digit)?;
-                            }
-                        }
-                        res.remove(i);
-                        for _ in 0..=b {
-                            res.remove(i);
-                        }
-                        res.insert(i, n)
-                    }
-                    _ => {}
+    let mut res = Vec::with_capacity(input.len());
+    let mut bytes = input.iter().copied().peekable();
+    while let Some(c) = bytes.next() {
+        if c != b'\\' {
+            res.push(c);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'\r') => {
+                bytes.next_if_eq(&b'\n');
+            }
+            Some(b'n') => res.push(b'\n'),
+            Some(b'r') => res.push(b'\r'),
+            Some(b't') => res.push(b'\t'),
+            Some(b'b') => res.push(0x08),
+            Some(b'f') => res.push(0x0C),
+            Some(o1 @ b'0'..=b'7') => {
+                let mut digits = vec![o1];
+                while digits.len() < 3
+                    && let Some(o) = bytes.next_if(|o| (b'0'..=b'7').contains(o))
+                {
+                    digits.push(o);
                 }
+                let mut n: u8 = 0;
+                for (exp, o) in (0u8..).zip(digits.iter().rev()) {
+                    n = n
+                        .checked_add(parse_octal(exp, *o)?)
+                        .ok_or(nom::error::ErrorKind::Digit)?;
+                }
+                res.push(n);
             }
+            // A backslash before an end-of-line marker or any other character is ignored.
+            Some(b'\n') | None => {}
+            Some(c) => res.push(c),
         }
     }
-
     Ok(String(res))
 }
 
@@ -162,13 +169,6 @@ fn fix_hex_str(s: &str) -> RString {
     s
 }
 
-fn is_ascii_digit(input: Option<&u8>) -> (bool, u8) {
-    if let Some(d) = input {
-        return (d.is_ascii_digit(), *d);
-    }
-    (false, 0)
-}
-
 fn parse_octal(exp: u8, ascii_number: u8) -> Result<u8, nom::error::ErrorKind> {
     if ascii_number.is_ascii_digit() {
         let number = ascii_number.saturating_sub(b'0');
@@ -300,6 +300,64 @@ mod tests {
         assert!(rem.is_empty());
         assert_eq!(parsed.get(), b"(");
     }
+    #[test]
+    fn write_string_1() {
+        let mut out = Vec::new();
+        String(b"a (nested) \\ string\n".to_vec())
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, b"(a \\(nested\\) \\\\ string\\n)");
+    }
+    #[test]
+    fn write_string_2() {
+        let mut out = Vec::new();
+        String(vec![0x90, 0x1f, 0xa3]).write_to(&mut out).unwrap();
+        assert_eq!(out, b"<901fa3>");
+    }
+    #[test]
+    fn write_string_3() {
+        let mut out = Vec::new();
+        String(b"caf\xe9".to_vec()).write_to(&mut out).unwrap();
+        assert_eq!(out, b"(caf\\351)");
+    }
+    #[test]
+    fn write_string_4() {
+        let inputs: [&[u8]; 5] = [
+            b"\\(",
+            b"\\n",
+            b"\r\n\t\x08\x0c\\\\",
+            b"\x00\x01\x02\x03 text with a few binary bytes",
+            b"",
+        ];
+        for input in inputs {
+            let mut out = Vec::new();
+            String(input.to_vec()).write_literal(&mut out).unwrap();
+            let (rem, parsed) = String::parse(&out).unwrap();
+            assert!(rem.is_empty());
+            assert_eq!(parsed.get(), input);
+        }
+    }
+    #[test]
+    fn parse_string_19() {
+        let (rem, parsed) = String::parse(b"(\\18\\q\\\r\nx)").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get(), b"\x018qx");
+    }
+    #[test]
+    fn parse_string_error_1() {
+        let err = match String::parse(b"<12zz>") {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "invalid hexadecimal string");
+        let err = match String::parse(b"(abc") {
+            Err(nom::Err::Error(err)) => Some(err),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(err.get_reason(), "unterminated literal string");
+    }
 }
 
 #[cfg(kani)]
@@ -0,0 +1,175 @@
+use super::String;
+
+type RString = std::string::String;
+
+const UTF16_BOM: &[u8] = b"\xfe\xff";
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const ESCAPE: char = '\u{1b}';
+
+impl String {
+    /// Decodes a text string, such as a document title or a form field value. Strings starting
+    /// with a UTF-16BE or UTF-8 byte order mark are decoded accordingly, all others as
+    /// PDFDocEncoding. Language escape sequences are removed from the text.
+    pub fn to_text(&self) -> RString {
+        let bytes = self.get();
+        if let Some(utf16) = bytes.strip_prefix(UTF16_BOM) {
+            let units = utf16
+                .chunks_exact(2)
+                .filter_map(|pair| pair.try_into().ok())
+                .map(u16::from_be_bytes);
+            let text = char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect();
+            return remove_language_escapes(text);
+        }
+        if let Some(utf8) = bytes.strip_prefix(UTF8_BOM) {
+            return remove_language_escapes(RString::from_utf8_lossy(utf8).into_owned());
+        }
+        bytes.iter().copied().map(pdf_doc_decode).collect()
+    }
+    /// Encodes text as a text string, in PDFDocEncoding when it can represent every character and
+    /// in UTF-16BE otherwise.
+    pub fn from_text(text: &str) -> Self {
+        let pdf_doc: Option<Vec<u8>> = text.chars().map(pdf_doc_encode).collect();
+        match pdf_doc {
+            Some(bytes) if !bytes.starts_with(UTF16_BOM) && !bytes.starts_with(UTF8_BOM) => {
+                Self::new(bytes)
+            }
+            _ => {
+                let mut bytes = UTF16_BOM.to_vec();
+                bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+                Self::new(bytes)
+            }
+        }
+    }
+}
+
+/// Removes the language escape sequences, each being a language code enclosed in two ESC
+/// characters.
+fn remove_language_escapes(text: RString) -> RString {
+    if !text.contains(ESCAPE) {
+        return text;
+    }
+    let mut escaped = false;
+    text.chars()
+        .filter(|c| {
+            if *c == ESCAPE {
+                escaped = !escaped;
+                return false;
+            }
+            !escaped
+        })
+        .collect()
+}
+
+/// Maps a PDFDocEncoding byte to its character. The few undefined codes are mapped to the
+/// character of the same value, as other readers do.
+pub fn pdf_doc_decode(byte: u8) -> char {
+    match byte {
+        0x18 => '\u{02d8}',
+        0x19 => '\u{02c7}',
+        0x1a => '\u{02c6}',
+        0x1b => '\u{02d9}',
+        0x1c => '\u{02dd}',
+        0x1d => '\u{02db}',
+        0x1e => '\u{02da}',
+        0x1f => '\u{02dc}',
+        0x80 => '\u{2022}',
+        0x81 => '\u{2020}',
+        0x82 => '\u{2021}',
+        0x83 => '\u{2026}',
+        0x84 => '\u{2014}',
+        0x85 => '\u{2013}',
+        0x86 => '\u{0192}',
+        0x87 => '\u{2044}',
+        0x88 => '\u{2039}',
+        0x89 => '\u{203a}',
+        0x8a => '\u{2212}',
+        0x8b => '\u{2030}',
+        0x8c => '\u{201e}',
+        0x8d => '\u{201c}',
+        0x8e => '\u{201d}',
+        0x8f => '\u{2018}',
+        0x90 => '\u{2019}',
+        0x91 => '\u{201a}',
+        0x92 => '\u{2122}',
+        0x93 => '\u{fb01}',
+        0x94 => '\u{fb02}',
+        0x95 => '\u{0141}',
+        0x96 => '\u{0152}',
+        0x97 => '\u{0160}',
+        0x98 => '\u{0178}',
+        0x99 => '\u{017d}',
+        0x9a => '\u{0131}',
+        0x9b => '\u{0142}',
+        0x9c => '\u{0153}',
+        0x9d => '\u{0161}',
+        0x9e => '\u{017e}',
+        0xa0 => '\u{20ac}',
+        _ => char::from(byte),
+    }
+}
+
+/// Maps a character to its PDFDocEncoding byte, if it has one.
+pub fn pdf_doc_encode(c: char) -> Option<u8> {
+    if let Ok(byte) = u8::try_from(c)
+        && pdf_doc_decode(byte) == c
+    {
+        return Some(byte);
+    }
+    (0x18..=0xa0).find(|byte| pdf_doc_decode(*byte) == c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn to_text_pdf_doc() {
+        let s = String::new(b"Caf\xe9 \x84 \x93nal \x18".to_vec());
+        assert_eq!(s.to_text(), "Café — ﬁnal ˘");
+    }
+    #[test]
+    fn to_text_utf16() {
+        let s = String::new(b"\xfe\xff\x00H\x00i\x00 \x26\x03\xd8\x3d\xde\x00".to_vec());
+        assert_eq!(s.to_text(), "Hi ☃😀");
+        let s = String::new(b"\xfe\xff\xd8\x3d\x00a\x00".to_vec());
+        assert_eq!(s.to_text(), "\u{fffd}a");
+    }
+    #[test]
+    fn to_text_utf8() {
+        let s = String::new("\u{feff}Grüße".as_bytes().to_vec());
+        assert_eq!(s.to_text(), "Grüße");
+    }
+    #[test]
+    fn to_text_language_escape() {
+        let s = String::new(b"\xfe\xff\x00\x1b\x00e\x00n\x00U\x00S\x00\x1b\x00H\x00i".to_vec());
+        assert_eq!(s.to_text(), "Hi");
+        let s = String::new("\u{feff}\u{1b}de\u{1b}Hallo".as_bytes().to_vec());
+        assert_eq!(s.to_text(), "Hallo");
+    }
+    #[test]
+    fn from_text_1() {
+        assert_eq!(
+            String::from_text("Café — ﬁnal").get(),
+            b"Caf\xe9 \x84 \x93nal"
+        );
+        assert_eq!(
+            String::from_text("Ωmega").get(),
+            b"\xfe\xff\x03\xa9\x00m\x00e\x00g\x00a"
+        );
+    }
+    #[test]
+    fn from_text_2() {
+        // Text that would look like a byte order mark in PDFDocEncoding.
+        let s = String::from_text("þÿ");
+        assert_eq!(s.get(), b"\xfe\xff\x00\xfe\x00\xff");
+        assert_eq!(s.to_text(), "þÿ");
+    }
+    #[test]
+    fn pdf_doc_round_trip() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(pdf_doc_encode(pdf_doc_decode(byte)), Some(byte));
+        }
+        assert_eq!(pdf_doc_encode('\u{80}'), None);
+    }
+}
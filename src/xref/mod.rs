@@ -0,0 +1,17 @@
+#![cfg_attr(
+    test,
+    allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+mod object_stream;
+mod section;
+mod startxref;
+mod stream;
+mod table;
+mod trailer;
+pub use object_stream::*;
+pub use section::*;
+pub use startxref::*;
+pub use stream::*;
+pub use table::*;
+pub use trailer::*;
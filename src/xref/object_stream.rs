@@ -0,0 +1,101 @@
+use nom::{Parser, multi::count, sequence::preceded};
+
+use crate::{
+    error::{Error, ParseError, Result},
+    lexer::{parse_unsigned, space0, space1},
+    limits::Limits,
+    objects::{GetObj, Integer, Name, Object, ObjectId, Stream},
+};
+
+/// The decoded contents of a compressed object stream (`/Type /ObjStm`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectStream {
+    data: Vec<u8>,
+    first: usize,
+    entries: Vec<(u32, usize)>,
+    limits: Limits,
+}
+
+impl ObjectStream {
+    pub fn new(stream: &Stream) -> Result<Self> {
+        Self::new_limited(stream, &Limits::default())
+    }
+    /// Decodes the stream and parses the objects it contains within `limits`.
+    pub fn new_limited(stream: &Stream, limits: &Limits) -> Result<Self> {
+        let info = stream.get_info();
+        let n = get_usize(info.get(&Name::new(b"N")))?;
+        if n > limits.max_objects {
+            return Err(Error::LimitExceeded("object count"));
+        }
+        let first = get_usize(info.get(&Name::new(b"First")))?;
+        let data = stream.decode_limited(limits)?;
+        let header = data.get(..first).ok_or(Error::InvalidObjectStream)?;
+        let (_, entries) = count(
+            preceded(space0, (parse_unsigned, preceded(space1, parse_unsigned))),
+            n,
+        )
+        .parse(header)
+        .map_err(|_: nom::Err<ParseError>| Error::InvalidObjectStream)?;
+        Ok(Self {
+            data,
+            first,
+            entries,
+            limits: *limits,
+        })
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns the object stored at position `index` of the stream.
+    pub fn get(&self, index: usize) -> Option<(ObjectId, Object<'_>)> {
+        let (number, offset) = self.entries.get(index)?;
+        let start = self.first.checked_add(*offset)?;
+        let (_, object) = preceded(space0, |i| Object::parse_limited(i, &self.limits))
+            .parse(self.data.get(start..)?)
+            .ok()?;
+        Some((ObjectId::new(*number, 0), object))
+    }
+    /// Returns the object with the given object number, if the stream contains it.
+    pub fn find(&self, number: u32) -> Option<Object<'_>> {
+        let index = self.entries.iter().position(|(n, _)| *n == number)?;
+        self.get(index).map(|(_, object)| object)
+    }
+}
+
+fn get_usize(object: Option<&Object>) -> Result<usize> {
+    let i: &Integer = object
+        .and_then(|o| o.get_obj())
+        .ok_or(Error::InvalidObjectStream)?;
+    usize::try_from(i.get()).map_err(|_| Error::InvalidObjectStream)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::{Boolean, Dictionary};
+
+    use super::*;
+    #[test]
+    fn object_stream_1() {
+        let input = b"<</Type /ObjStm /N 3 /First 16 /Length 37>>stream\n11 0 12 9 13 17 <</A 1>> [1 2 3] true\nendstream";
+        let (_, stream) = Stream::parse(input).unwrap();
+        let parsed = ObjectStream::new(&stream).unwrap();
+        assert_eq!(parsed.len(), 3);
+        let (id, obj) = parsed.get(0).unwrap();
+        assert_eq!(id, ObjectId::new(11, 0));
+        let _: &Dictionary = obj.get_obj().unwrap();
+        let obj = parsed.find(13).unwrap();
+        let b: &Boolean = obj.get_obj().unwrap();
+        assert!(b.get());
+        assert!(parsed.find(14).is_none());
+        assert!(parsed.get(3).is_none());
+    }
+    #[test]
+    fn object_stream_2() {
+        let input = b"<</Type /ObjStm /N 2 /First 5 /Length 6>>stream\n1 0 42\nendstream";
+        let (_, stream) = Stream::parse(input).unwrap();
+        assert!(ObjectStream::new(&stream).is_err());
+    }
+}
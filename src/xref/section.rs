@@ -0,0 +1,48 @@
+use nom::{Parser, sequence::preceded};
+
+use super::{Trailer, XrefTable};
+use crate::{error::ParseResult, lexer::space0};
+
+/// A classic cross-reference section: the `xref` table followed by its `trailer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XrefSection<'b> {
+    table: XrefTable,
+    trailer: Trailer<'b>,
+}
+
+impl<'b> XrefSection<'b> {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, XrefSection<'b>> {
+        (XrefTable::parse, preceded(space0, Trailer::parse))
+            .map(|(table, trailer)| XrefSection { table, trailer })
+            .parse(input)
+    }
+    pub fn get_table(&self) -> &XrefTable {
+        &self.table
+    }
+    pub fn get_trailer(&self) -> &Trailer<'b> {
+        &self.trailer
+    }
+    pub fn into_parts(self) -> (XrefTable, Trailer<'b>) {
+        (self.table, self.trailer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::ObjectId;
+
+    use super::*;
+    #[test]
+    fn parse_section_1() {
+        let input = b"xref\n0 2\n0000000000 65535 f \n0000000009 00000 n \ntrailer\n<</Size 2 /Root 1 0 R>>\nstartxref\n";
+        let (rem, parsed) = XrefSection::parse(input).unwrap();
+        assert_eq!(rem, b"\nstartxref\n");
+        assert_eq!(parsed.get_table().offset(ObjectId::new(1, 0)), Some(9));
+        assert_eq!(parsed.get_trailer().root(), Some(ObjectId::new(1, 0)));
+    }
+    #[test]
+    fn parse_section_2() {
+        let input = b"xref\n0 1\n0000000000 65535 f \n";
+        assert!(XrefSection::parse(input).is_err());
+    }
+}
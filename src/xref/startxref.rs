@@ -0,0 +1,54 @@
+use nom::{Parser, sequence::preceded};
+
+use crate::{
+    error::ParseResult,
+    lexer::{keyword, parse_unsigned, space1},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartXref(usize);
+
+impl StartXref {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, StartXref> {
+        preceded((keyword("startxref"), space1), parse_unsigned)
+            .map(StartXref)
+            .parse(input)
+    }
+    /// Locates the last `startxref` keyword of the file and parses the offset following it.
+    pub fn find(input: &[u8]) -> Option<StartXref> {
+        let keyword = b"startxref";
+        let position = input
+            .windows(keyword.len())
+            .rposition(|window| window == keyword)?;
+        let (_, parsed) = Self::parse(input.get(position..)?).ok()?;
+        Some(parsed)
+    }
+    pub fn get(&self) -> usize {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn parse_startxref_1() {
+        let (rem, parsed) = StartXref::parse(b"startxref\n18799\n%%EOF").unwrap();
+        assert_eq!(rem, b"\n%%EOF");
+        assert_eq!(parsed.get(), 18799);
+    }
+    #[test]
+    fn parse_startxref_2() {
+        let parsed = StartXref::parse(b"startxref\n%%EOF");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn find_startxref_1() {
+        let input = b"%PDF-1.4\n...startxref\n10\n%%EOF\n...startxref\r\n116\r\n%%EOF\r\n";
+        assert_eq!(StartXref::find(input), Some(StartXref(116)));
+    }
+    #[test]
+    fn find_startxref_2() {
+        assert_eq!(StartXref::find(b"%PDF-1.4\n%%EOF"), None);
+    }
+}
@@ -0,0 +1,222 @@
+use nom::{Parser, error::context};
+
+use super::{Trailer, XrefEntry, XrefTable};
+use crate::{
+    error::{Error, ParseResult, Result},
+    limits::Limits,
+    objects::{Array, GetObj, IndirectObject, Integer, Name, Object, ObjectId, Stream},
+};
+
+/// A cross-reference stream (`/Type /XRef`) whose dictionary doubles as the trailer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XrefStream<'b> {
+    id: ObjectId,
+    table: XrefTable,
+    trailer: Trailer<'b>,
+}
+
+impl<'b> XrefStream<'b> {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, XrefStream<'b>> {
+        Self::parse_limited(input, &Limits::default())
+    }
+    /// Parses the stream object and decodes its entries within `limits`.
+    pub fn parse_limited(input: &'b [u8], limits: &Limits) -> ParseResult<'b, XrefStream<'b>> {
+        context(
+            "invalid cross-reference stream",
+            (|input| IndirectObject::parse_limited(input, limits)).map_res(|object| {
+                let id = object.get_id();
+                match object.into_object() {
+                    Object::Stream(stream) => Self::new_limited(id, stream, limits),
+                    _ => Err(Error::InvalidXrefStream),
+                }
+            }),
+        )
+        .parse(input)
+    }
+    pub fn new(id: ObjectId, stream: Stream<'b>) -> Result<Self> {
+        Self::new_limited(id, stream, &Limits::default())
+    }
+    /// Reads the entries of a cross-reference stream, decoding it within `limits`.
+    pub fn new_limited(id: ObjectId, stream: Stream<'b>, limits: &Limits) -> Result<Self> {
+        let info = stream.get_info();
+        let widths = get_integers(info.get(&Name::new(b"W")))?;
+        let [w1, w2, w3] = widths[..] else {
+            return Err(Error::InvalidXrefStream);
+        };
+        let size: &Integer = info
+            .get(&Name::new(b"Size"))
+            .and_then(|s| s.get_obj())
+            .ok_or(Error::InvalidXrefStream)?;
+        let size = usize::try_from(size.get()).map_err(|_| Error::InvalidXrefStream)?;
+        let index = match info.get(&Name::new(b"Index")) {
+            Some(index) => get_integers(Some(index))?,
+            None => vec![0, size],
+        };
+        let entry_length = w1
+            .checked_add(w2)
+            .and_then(|w| w.checked_add(w3))
+            .filter(|w| *w > 0)
+            .ok_or(Error::InvalidXrefStream)?;
+        let data = stream.decode_limited(limits)?;
+        let mut entries = data.chunks_exact(entry_length);
+        let mut table = XrefTable::new();
+        for subsection in index.chunks(2) {
+            let [start, count] = subsection[..] else {
+                return Err(Error::InvalidXrefStream);
+            };
+            for number in (start..).take(count) {
+                let Some(entry) = entries.next() else {
+                    break;
+                };
+                let number = u32::try_from(number).map_err(|_| Error::InvalidXrefStream)?;
+                if let Some(entry) = parse_entry(entry, w1, w2)? {
+                    table.insert(number, entry);
+                }
+            }
+        }
+        Ok(Self {
+            id,
+            table,
+            trailer: Trailer::new(stream.info),
+        })
+    }
+    pub fn get_id(&self) -> ObjectId {
+        self.id
+    }
+    pub fn get_table(&self) -> &XrefTable {
+        &self.table
+    }
+    pub fn get_trailer(&self) -> &Trailer<'b> {
+        &self.trailer
+    }
+    pub fn into_parts(self) -> (XrefTable, Trailer<'b>) {
+        (self.table, self.trailer)
+    }
+}
+
+fn get_integers(object: Option<&Object>) -> Result<Vec<usize>> {
+    let array: &Array = object
+        .and_then(|o| o.get_obj())
+        .ok_or(Error::InvalidXrefStream)?;
+    array
+        .get()
+        .iter()
+        .map(|o| {
+            let i: &Integer = o.get_obj().ok_or(Error::InvalidXrefStream)?;
+            usize::try_from(i.get()).map_err(|_| Error::InvalidXrefStream)
+        })
+        .collect()
+}
+
+fn parse_entry(entry: &[u8], w1: usize, w2: usize) -> Result<Option<XrefEntry>> {
+    let (kind, rest) = entry.split_at_checked(w1).ok_or(Error::InvalidXrefStream)?;
+    let (field2, field3) = rest.split_at_checked(w2).ok_or(Error::InvalidXrefStream)?;
+    let kind = if w1 == 0 { 1 } else { read_field(kind)? };
+    let field2 = read_field(field2)?;
+    let field3 = read_field(field3)?;
+    let overflow = |_| Error::InvalidXrefStream;
+    Ok(match kind {
+        0 => Some(XrefEntry::Free {
+            next: field2.try_into().map_err(overflow)?,
+            generation: field3.try_into().map_err(overflow)?,
+        }),
+        1 => Some(XrefEntry::InUse {
+            offset: field2.try_into().map_err(overflow)?,
+            generation: field3.try_into().map_err(overflow)?,
+        }),
+        2 => Some(XrefEntry::Compressed {
+            stream: field2.try_into().map_err(overflow)?,
+            index: field3.try_into().map_err(overflow)?,
+        }),
+        // Unknown types are to be treated as references to the null object.
+        _ => None,
+    })
+}
+
+fn read_field(bytes: &[u8]) -> Result<u64> {
+    bytes.iter().try_fold(0u64, |acc, b| {
+        acc.checked_mul(256)
+            .and_then(|acc| acc.checked_add(u64::from(*b)))
+            .ok_or(Error::InvalidXrefStream)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xref_stream(dict: &str, data: &[u8]) -> Vec<u8> {
+        let mut res = format!(
+            "5 0 obj\n{}stream\n",
+            dict.replace("LEN", &data.len().to_string())
+        )
+        .into_bytes();
+        res.extend_from_slice(data);
+        res.extend_from_slice(b"\nendstream\nendobj");
+        res
+    }
+    #[test]
+    fn parse_xref_stream_1() {
+        let data = [0, 0, 0, 255, 1, 0, 15, 0, 1, 0, 99, 0, 2, 0, 5, 3];
+        let input = xref_stream(
+            "<</Type /XRef /Size 4 /W [1 2 1] /Root 1 0 R /Length LEN>>",
+            &data,
+        );
+        let (rem, parsed) = XrefStream::parse(&input).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.get_id(), ObjectId::new(5, 0));
+        let table = parsed.get_table();
+        assert_eq!(
+            table.get(0),
+            Some(&XrefEntry::Free {
+                next: 0,
+                generation: 255
+            })
+        );
+        assert_eq!(table.offset(ObjectId::new(1, 0)), Some(15));
+        assert_eq!(table.offset(ObjectId::new(2, 0)), Some(99));
+        assert_eq!(
+            table.get(3),
+            Some(&XrefEntry::Compressed {
+                stream: 5,
+                index: 3
+            })
+        );
+        assert_eq!(parsed.get_trailer().root(), Some(ObjectId::new(1, 0)));
+    }
+    #[test]
+    fn parse_xref_stream_2() {
+        let data = [0, 10, 0, 20];
+        let input = xref_stream(
+            "<</Type /XRef /Size 12 /Index [3 1 10 1] /W [0 2 0] /Length LEN>>",
+            &data,
+        );
+        let (_, parsed) = XrefStream::parse(&input).unwrap();
+        let table = parsed.get_table();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.offset(ObjectId::new(3, 0)), Some(10));
+        assert_eq!(table.offset(ObjectId::new(10, 0)), Some(20));
+    }
+    #[test]
+    fn parse_xref_stream_3() {
+        // Columns 4 with PNG Up prediction, deflated.
+        let data = [
+            0x78, 0x9c, 0x63, 0x62, 0x64, 0x10, 0x60, 0x60, 0x62, 0x60, 0x64, 0x60, 0x00, 0x00,
+            0x00, 0xa4, 0x00, 0x17,
+        ];
+        let input = xref_stream(
+            "<</Type /XRef /Size 2 /W [1 2 1] /Filter /FlateDecode /DecodeParms <</Predictor 12 /Columns 4>> /Prev 7 /Length LEN>>",
+            &data,
+        );
+        let (_, parsed) = XrefStream::parse(&input).unwrap();
+        let table = parsed.get_table();
+        assert_eq!(table.offset(ObjectId::new(0, 0)), Some(16));
+        assert_eq!(table.offset(ObjectId::new(1, 0)), Some(272));
+        assert_eq!(parsed.get_trailer().prev(), Some(7));
+    }
+    #[test]
+    fn parse_xref_stream_4() {
+        let input = xref_stream("<</Type /XRef /Size 1 /W [1 2] /Length LEN>>", &[1, 0, 0]);
+        assert!(XrefStream::parse(&input).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, multispace0, multispace1, space0, space1},
+    error::context,
+    multi::{count, many0},
+    sequence::{preceded, terminated},
+};
+
+use crate::{
+    error::ParseResult,
+    lexer::parse_unsigned,
+    objects::{IndirectObject, Object, ObjectId},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrefEntry {
+    Free { next: u32, generation: u16 },
+    InUse { offset: usize, generation: u16 },
+    Compressed { stream: u32, index: u32 },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XrefTable(BTreeMap<u32, XrefEntry>);
+
+impl XrefEntry {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, XrefEntry> {
+        context(
+            "invalid cross-reference entry",
+            (
+                terminated(parse_unsigned::<u64>, space1),
+                terminated(parse_unsigned::<u16>, space1),
+                terminated(alt((char('n'), char('f'))), multispace0),
+            )
+                .map_opt(|(field, generation, kind)| match kind {
+                    'n' => Some(XrefEntry::InUse {
+                        offset: field.try_into().ok()?,
+                        generation,
+                    }),
+                    _ => Some(XrefEntry::Free {
+                        next: field.try_into().ok()?,
+                        generation,
+                    }),
+                }),
+        )
+        .parse(input)
+    }
+    pub fn generation(&self) -> u16 {
+        match self {
+            XrefEntry::Free { generation, .. } | XrefEntry::InUse { generation, .. } => *generation,
+            XrefEntry::Compressed { .. } => 0,
+        }
+    }
+}
+
+impl XrefTable {
+    pub fn parse(input: &[u8]) -> ParseResult<'_, XrefTable> {
+        preceded(
+            terminated(tag("xref"), multispace1),
+            many0(parse_subsection),
+        )
+        .map(|subsections| {
+            let mut table = XrefTable::default();
+            for (number, entry) in subsections.into_iter().flatten() {
+                table.0.insert(number, entry);
+            }
+            table
+        })
+        .parse(input)
+    }
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, number: u32) -> Option<&XrefEntry> {
+        self.0.get(&number)
+    }
+    pub fn insert(&mut self, number: u32, entry: XrefEntry) {
+        self.0.insert(number, entry);
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&u32, &XrefEntry)> {
+        self.0.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Adds the entries of an older section that are not overridden by this one.
+    pub fn merge_older(&mut self, older: XrefTable) {
+        for (number, entry) in older.0 {
+            self.0.entry(number).or_insert(entry);
+        }
+    }
+    pub fn offset(&self, id: ObjectId) -> Option<usize> {
+        match self.get(id.number())? {
+            XrefEntry::InUse { offset, generation } if *generation == id.generation() => {
+                Some(*offset)
+            }
+            _ => None,
+        }
+    }
+    /// Seeks to the offset recorded for `id` and parses the object defined there.
+    pub fn resolve<'b>(&self, input: &'b [u8], id: ObjectId) -> Option<Object<'b>> {
+        let (_, object) = IndirectObject::parse(input.get(self.offset(id)?..)?).ok()?;
+        if object.get_id() != id {
+            return None;
+        }
+        Some(object.into_object())
+    }
+}
+
+fn parse_subsection(input: &[u8]) -> ParseResult<'_, Vec<(u32, XrefEntry)>> {
+    let (input, (start, length)) = (
+        terminated(parse_unsigned::<u32>, space1),
+        terminated(parse_unsigned::<usize>, (space0, multispace1)),
+    )
+        .parse(input)?;
+    count(XrefEntry::parse, length)
+        .map_opt(|entries| {
+            entries
+                .into_iter()
+                .zip(0u32..)
+                .map(|(entry, i)| Some((start.checked_add(i)?, entry)))
+                .collect::<Option<Vec<_>>>()
+        })
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::{GetObj, Integer};
+
+    use super::*;
+    #[test]
+    fn parse_entry_1() {
+        let (rem, parsed) = XrefEntry::parse(b"0000000017 00000 n\r\n").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            parsed,
+            XrefEntry::InUse {
+                offset: 17,
+                generation: 0
+            }
+        );
+    }
+    #[test]
+    fn parse_entry_2() {
+        let (rem, parsed) = XrefEntry::parse(b"0000000000 65535 f \n").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            parsed,
+            XrefEntry::Free {
+                next: 0,
+                generation: 65535
+            }
+        );
+    }
+    #[test]
+    fn parse_entry_3() {
+        let parsed = XrefEntry::parse(b"0000000000 65535 x \n");
+        assert!(parsed.is_err());
+    }
+    #[test]
+    fn parse_table_1() {
+        let input =
+            b"xref\n0 3\n0000000000 65535 f \n0000000017 00000 n \n0000000081 00000 n \ntrailer";
+        let (rem, parsed) = XrefTable::parse(input).unwrap();
+        assert_eq!(rem, b"trailer");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed.offset(ObjectId::new(1, 0)), Some(17));
+        assert_eq!(parsed.offset(ObjectId::new(2, 0)), Some(81));
+        assert_eq!(parsed.offset(ObjectId::new(2, 1)), None);
+        assert_eq!(parsed.offset(ObjectId::new(0, 65535)), None);
+    }
+    #[test]
+    fn parse_table_2() {
+        let input = b"xref\r\n0 1\r\n0000000000 65535 f\r\n3 2\r\n0000025325 00000 n\r\n0000025518 00002 n\r\n";
+        let (rem, parsed) = XrefTable::parse(input).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.len(), 3);
+        assert!(parsed.get(1).is_none());
+        assert_eq!(parsed.offset(ObjectId::new(3, 0)), Some(25325));
+        assert_eq!(parsed.offset(ObjectId::new(4, 2)), Some(25518));
+    }
+    #[test]
+    fn merge_table_1() {
+        let (_, mut newer) = XrefTable::parse(b"xref\n1 1\n0000000030 00000 n \n").unwrap();
+        let (_, older) =
+            XrefTable::parse(b"xref\n1 2\n0000000010 00000 n \n0000000020 00000 n \n").unwrap();
+        newer.merge_older(older);
+        assert_eq!(newer.offset(ObjectId::new(1, 0)), Some(30));
+        assert_eq!(newer.offset(ObjectId::new(2, 0)), Some(20));
+    }
+    #[test]
+    fn resolve_1() {
+        let input = b"%PDF-1.4\n1 0 obj 42 endobj\n";
+        let mut table = XrefTable::new();
+        table.insert(
+            1,
+            XrefEntry::InUse {
+                offset: 9,
+                generation: 0,
+            },
+        );
+        let obj = table.resolve(input, ObjectId::new(1, 0)).unwrap();
+        let i: &Integer = obj.get_obj().unwrap();
+        assert_eq!(i.get(), 42);
+        assert!(table.resolve(input, ObjectId::new(2, 0)).is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use nom::{Parser, sequence::preceded};
+
+use crate::{
+    error::ParseResult,
+    lexer::{keyword, space0},
+    objects::{Array, Dictionary, GetObj, Integer, Name, Object, ObjectId, Reference, String},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trailer<'b>(Dictionary<'b>);
+
+impl<'b> Trailer<'b> {
+    pub fn parse(input: &'b [u8]) -> ParseResult<'b, Trailer<'b>> {
+        preceded((keyword("trailer"), space0), Dictionary::parse)
+            .map(Trailer)
+            .parse(input)
+    }
+    pub fn new(dictionary: Dictionary<'b>) -> Self {
+        Self(dictionary)
+    }
+    pub fn get(&self) -> &Dictionary<'b> {
+        &self.0
+    }
+    pub fn size(&self) -> Option<u32> {
+        self.get_integer(b"Size")?.get().try_into().ok()
+    }
+    pub fn prev(&self) -> Option<usize> {
+        self.get_integer(b"Prev")?.get().try_into().ok()
+    }
+    pub fn root(&self) -> Option<ObjectId> {
+        self.get_reference(b"Root")
+    }
+    pub fn info(&self) -> Option<ObjectId> {
+        self.get_reference(b"Info")
+    }
+    /// The offset of the cross-reference stream of a hybrid-reference file.
+    pub fn xref_stream(&self) -> Option<usize> {
+        self.get_integer(b"XRefStm")?.get().try_into().ok()
+    }
+    pub fn encrypt(&self) -> Option<&Object<'b>> {
+        self.0.get(&Name::new(b"Encrypt"))
+    }
+    /// The first element of the file identifier.
+    pub fn id(&self) -> Option<&[u8]> {
+        let id: &Array = self.0.get(&Name::new(b"ID"))?.get_obj()?;
+        let first: &String = id.get().first()?.get_obj()?;
+        Some(first.get())
+    }
+    fn get_integer(&self, key: &'b [u8]) -> Option<&Integer> {
+        self.0.get(&Name::new(key))?.get_obj()
+    }
+    fn get_reference(&self, key: &'b [u8]) -> Option<ObjectId> {
+        let reference: &Reference = self.0.get(&Name::new(key))?.get_obj()?;
+        Some(reference.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn parse_trailer_1() {
+        let (rem, parsed) =
+            Trailer::parse(b"trailer\n<< /Size 22 /Root 2 0 R /Info 1 0 R /Prev 1234 >>").unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.size(), Some(22));
+        assert_eq!(parsed.root(), Some(ObjectId::new(2, 0)));
+        assert_eq!(parsed.info(), Some(ObjectId::new(1, 0)));
+        assert_eq!(parsed.prev(), Some(1234));
+    }
+    #[test]
+    fn parse_trailer_2() {
+        let (rem, parsed) = Trailer::parse(b"trailer<</Size 3>>\n").unwrap();
+        assert_eq!(rem, b"\n");
+        assert_eq!(parsed.size(), Some(3));
+        assert_eq!(parsed.prev(), None);
+        assert_eq!(parsed.root(), None);
+    }
+    #[test]
+    fn parse_trailer_3() {
+        let parsed = Trailer::parse(b"<</Size 3>>");
+        assert!(parsed.is_err());
+    }
+}